@@ -0,0 +1,252 @@
+use std::time::{Duration, Instant};
+
+use fxhash::FxHashMap;
+use itertools::Itertools;
+
+use crate::{
+    board::{Board, Position},
+    geometry::Geometry,
+    versus::{Outcome, Play},
+};
+
+/// Scores at least this are won, at most minus this are lost, anything between is a guess
+const WIN: i32 = 1_000_000;
+
+/// How hard the computer tries
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Strength {
+    Easy,
+    Medium,
+    Hard,
+    Perfect,
+}
+
+impl Strength {
+    pub const ALL: [Strength; 4] = [
+        Strength::Easy,
+        Strength::Medium,
+        Strength::Hard,
+        Strength::Perfect,
+    ];
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|s| format!("{:?}", s).eq_ignore_ascii_case(name))
+    }
+    /// Deepest search in jumps and the time allowed for one move
+    fn limits(self) -> (usize, Duration) {
+        match self {
+            Strength::Easy => (1, Duration::from_millis(10)),
+            Strength::Medium => (3, Duration::from_millis(100)),
+            Strength::Hard => (8, Duration::from_secs(1)),
+            Strength::Perfect => (usize::MAX, Duration::from_secs(5)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    /// The score is at least `value`
+    Lower,
+    /// The score is at most `value`
+    Upper,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Entry<G: Geometry> {
+    depth: usize,
+    value: i32,
+    bound: Bound,
+    /// Canonical board after the best jump, tried first next time
+    best: Option<Board<G>>,
+}
+
+/// A jump picked by the computer
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Decision {
+    pub jump: (Position, Position, Position),
+    /// Score for the player making the jump
+    pub value: i32,
+    /// Deepest search that finished in time
+    pub depth: usize,
+}
+
+impl Decision {
+    /// Known result with perfect play, None if the search only got far enough to guess
+    pub fn outcome(&self) -> Option<Outcome> {
+        if self.value >= WIN {
+            Some(Outcome::Win)
+        } else if self.value <= -WIN {
+            Some(Outcome::Loss)
+        } else {
+            None
+        }
+    }
+}
+
+/// Computer player for the two player game: negamax with alpha-beta pruning and iterative
+/// deepening, with a transposition table that lasts between moves
+#[derive(Debug, Clone)]
+pub struct Opponent<G: Geometry> {
+    play: Play,
+    strength: Strength,
+    /// Keyed by canonical board, both players have the same jumps so symmetries share entries
+    table: FxHashMap<Board<G>, Entry<G>>,
+}
+
+impl<G: Geometry> Opponent<G> {
+    pub fn new(play: Play, strength: Strength) -> Self {
+        Self {
+            play,
+            strength,
+            table: FxHashMap::default(),
+        }
+    }
+    pub fn strength(&self) -> Strength {
+        self.strength
+    }
+
+    /// Best jump found within the strength's limits, None if there is no jump
+    pub fn choose(&mut self, board: &Board<G>) -> Option<Decision> {
+        let (max_depth, budget) = self.strength.limits();
+        let deadline = Instant::now() + budget;
+        let jumps = board.all_valid_moves().collect_vec();
+        let mut best = None;
+        // Every jump removes a stone, so the game can't last longer than that
+        for depth in 1..=max_depth.min(board.count()) {
+            match self.root(board, &jumps, depth, deadline) {
+                Some(decision) => {
+                    best = Some(decision);
+                    if decision.outcome().is_some() {
+                        break;
+                    }
+                }
+                None => break,
+            }
+        }
+        // Always answer, even if not even depth 1 finished
+        best.or_else(|| {
+            jumps.first().map(|&jump| Decision {
+                jump,
+                value: 0,
+                depth: 0,
+            })
+        })
+    }
+    fn root(
+        &mut self,
+        board: &Board<G>,
+        jumps: &[(Position, Position, Position)],
+        depth: usize,
+        deadline: Instant,
+    ) -> Option<Decision> {
+        let mut alpha = -WIN * 2;
+        let mut best = None;
+        for &jump in jumps {
+            let (from, _, to) = jump;
+            let next = board.apply_move(from, to).expect("Valid by construction");
+            let value = -self.negamax(&next, depth - 1, -WIN * 2, -alpha, deadline)?;
+            if best.is_none() || value > alpha {
+                alpha = value;
+                best = Some(Decision { jump, value, depth });
+            }
+        }
+        best
+    }
+    /// Score for the player to move, None if time ran out
+    fn negamax(
+        &mut self,
+        board: &Board<G>,
+        depth: usize,
+        mut alpha: i32,
+        mut beta: i32,
+        deadline: Instant,
+    ) -> Option<i32> {
+        if Instant::now() > deadline {
+            return None;
+        }
+        let key = board.canonicalize();
+        let entry = self.table.get(&key).copied();
+        if let Some(entry) = entry.filter(|e| e.depth >= depth) {
+            match entry.bound {
+                Bound::Exact => return Some(entry.value),
+                Bound::Lower => alpha = alpha.max(entry.value),
+                Bound::Upper => beta = beta.min(entry.value),
+            }
+            if alpha >= beta {
+                return Some(entry.value);
+            }
+        }
+
+        let mut children = board
+            .all_valid_moves()
+            .map(|(from, _, to)| board.apply_move(from, to).expect("Valid by construction"))
+            .collect_vec();
+        if children.is_empty() {
+            // Stuck, finishing with more stones left means a shorter game
+            let value = WIN + board.count() as i32;
+            return Some(match self.play {
+                Play::Normal => -value,
+                Play::Misere => value,
+            });
+        }
+        if depth == 0 {
+            return Some(self.evaluate(board, children.len()));
+        }
+        if let Some(i) = entry
+            .and_then(|e| e.best)
+            .and_then(|best| children.iter().position(|c| c.canonicalize() == best))
+        {
+            children.swap(0, i);
+        }
+
+        let alpha0 = alpha;
+        let mut value = -WIN * 2;
+        let mut best = None;
+        for child in children {
+            let score = -self.negamax(&child, depth - 1, -beta, -alpha, deadline)?;
+            if score > value {
+                value = score;
+                best = Some(child.canonicalize());
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        let bound = if value <= alpha0 {
+            Bound::Upper
+        } else if value >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.insert(
+            key,
+            Entry {
+                depth,
+                value,
+                bound,
+                best,
+            },
+        );
+        Some(value)
+    }
+
+    /// Guess for boards too deep to search. Stones with a neighbour can still take part in a
+    /// jump, roughly half that many jumps are left and their parity decides the game
+    fn evaluate(&self, board: &Board<G>, jumps: usize) -> i32 {
+        let live = board
+            .iter_stones()
+            .filter(|&p| Board::<G>::adjacent(p).any(|q| board.at(q) == Some(true)))
+            .count();
+        let odd = (live / 2) % 2 == 1;
+        let parity = match (self.play, odd) {
+            (Play::Normal, true) | (Play::Misere, false) => 100,
+            _ => -100,
+        };
+        parity + jumps as i32
+    }
+}
@@ -0,0 +1,161 @@
+use std::{fmt, iter};
+
+use itertools::Itertools;
+
+use crate::{
+    board::Board,
+    geometry::Geometry,
+    goal::Goal,
+    solver::Solver,
+    strategy::{Jump, Perfect},
+};
+
+/// A board of a finished game and what perfect play could still do from it
+#[derive(Debug, Clone)]
+pub struct Step<G: Geometry> {
+    pub board: Board<G>,
+    /// The jump made from this board, None for the last one
+    pub jump: Option<Jump>,
+    /// The goal could still be reached
+    pub solvable: bool,
+    /// Fewest stones perfect play would leave from here
+    pub fewest: usize,
+}
+
+/// The first jump that lost the goal
+#[derive(Debug, Clone)]
+pub struct Blunder {
+    /// Index into `Analysis::steps` of the board the jump was made from
+    pub step: usize,
+    pub jump: Jump,
+    /// The jumps that would have kept the goal
+    pub alternatives: Vec<Jump>,
+}
+
+/// Replay of a game against perfect play
+#[derive(Debug, Clone)]
+pub struct Analysis<G: Geometry> {
+    pub goal: Goal<G>,
+    pub steps: Vec<Step<G>>,
+    pub blunder: Option<Blunder>,
+}
+
+/// Go through the boards of a game in order, None if one doesn't follow from the last by a jump
+pub fn analyse<G: Geometry>(boards: &[Board<G>], goal: Goal<G>) -> Option<Analysis<G>> {
+    let mut solver = Solver::new(goal);
+    let mut perfect = Perfect::default();
+    let steps = boards
+        .iter()
+        .enumerate()
+        .map(|(i, board)| {
+            let jump = match boards.get(i + 1) {
+                Some(next) => Some(board.jump_to(next)?),
+                None => None,
+            };
+            Some(Step {
+                board: *board,
+                jump,
+                solvable: solver.solvable(board),
+                fewest: perfect.fewest(board),
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let blunder = steps
+        .iter()
+        .tuple_windows()
+        .position(|(before, after)| before.solvable && !after.solvable)
+        .map(|step| {
+            let board = steps[step].board;
+            Blunder {
+                step,
+                jump: steps[step].jump.expect("Not the last board"),
+                alternatives: board
+                    .all_valid_moves()
+                    .filter(|&(from, _, to)| {
+                        let next = board.apply_move(from, to).expect("Valid by construction");
+                        solver.solvable(&next)
+                    })
+                    .collect(),
+            }
+        });
+    Some(Analysis {
+        goal,
+        steps,
+        blunder,
+    })
+}
+
+fn jump_text((from, over, to): Jump) -> String {
+    format!(
+        "({}, {}) over ({}, {}) to ({}, {})",
+        from.x, from.y, over.x, over.y, to.x, to.y
+    )
+}
+
+fn step_text<G: Geometry>(i: usize, step: &Step<G>) -> String {
+    let jump = match step.jump {
+        Some(jump) => format!("{:>3}. {}", i + 1, jump_text(jump)),
+        None => "     end".to_owned(),
+    };
+    format!(
+        "{}, {}, perfect play leaves {}",
+        jump,
+        if step.solvable { "solvable" } else { "unsolvable" },
+        step.fewest
+    )
+}
+
+fn blunder_text(blunder: &Blunder) -> String {
+    format!("Jump {} lost the goal: {}", blunder.step + 1, jump_text(blunder.jump))
+}
+
+impl<G: Geometry> Analysis<G> {
+    /// A line per board, then the jump that lost the goal with the ones that would have kept
+    /// it, then the summary
+    pub fn report(&self) -> Vec<String> {
+        let steps = self.steps.iter().enumerate().map(|(i, step)| step_text(i, step));
+        let blunder = self.blunder.iter().flat_map(|blunder| {
+            iter::once(blunder_text(blunder)).chain(
+                blunder
+                    .alternatives
+                    .iter()
+                    .map(|&jump| format!("  instead {}", jump_text(jump))),
+            )
+        });
+        steps.chain(blunder).chain(iter::once(self.summary())).collect()
+    }
+    /// One line summary
+    pub fn summary(&self) -> String {
+        let last = self.steps.last().map_or(0, |step| step.board.count());
+        let best = self.steps.first().map_or(0, |step| step.fewest);
+        match &self.blunder {
+            Some(blunder) => format!(
+                "Left {} stones, could have left {}, goal lost at jump {}",
+                last,
+                best,
+                blunder.step + 1
+            ),
+            None => format!("Left {} stones, could have left {}", last, best),
+        }
+    }
+}
+
+impl<G: Geometry> fmt::Display for Analysis<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Goal {:?}", self.goal)?;
+        if let Some(start) = self.steps.first() {
+            write!(f, "{}", start.board)?;
+        }
+        for (i, step) in self.steps.iter().enumerate() {
+            writeln!(f, "{}", step_text(i, step))?;
+        }
+        if let Some(blunder) = &self.blunder {
+            writeln!(f, "{}", blunder_text(blunder))?;
+            write!(f, "{}", self.steps[blunder.step].board)?;
+            for &jump in &blunder.alternatives {
+                writeln!(f, "  instead {}", jump_text(jump))?;
+            }
+        }
+        writeln!(f, "{}", self.summary())
+    }
+}
@@ -2,14 +2,22 @@ use boolinator::Boolinator;
 use itertools::{izip, Itertools};
 use std::{
     convert::{TryFrom, TryInto},
+    fmt,
     iter::{self, FromIterator},
-    mem::size_of_val,
+    marker::PhantomData,
     num::TryFromIntError,
+    str::FromStr,
 };
 
-use crate::u32set::FixedBitSet;
+use smallvec::SmallVec;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+use crate::{
+    geometry::{Geometry, Symmetry},
+    rules::Capture,
+    u32set::FixedBitSet,
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Position {
     pub x: u8,
     pub y: u8,
@@ -22,6 +30,11 @@ impl Position {
     pub fn from((x, y): (u8, u8)) -> Self {
         Position { x, y }
     }
+    /// `x,y`
+    pub fn parse(s: &str) -> Option<Self> {
+        let (x, y) = s.split(',').map(|c| c.trim().parse().ok()).collect_tuple()?;
+        Some(Position::new(x?, y?))
+    }
 }
 pub fn pos(x: u8, y: u8) -> Position {
     Position::new(x, y)
@@ -55,33 +68,114 @@ where
     }
 }
 
+/// Candidate three-colourings `(a * x + b * y) % 3` of a lattice
+const COLOURINGS: [(u8, u8); 4] = [(1, 0), (0, 1), (1, 1), (1, 2)];
+
+/// The colourings where every jump along `directions` touches three different colours.
+/// On the triangular lattice that is `(x + 2 * y) % 3`, the square lattice has two
+pub fn colourings(directions: &[(i8, i8)]) -> impl Iterator<Item = (u8, u8)> + '_ {
+    COLOURINGS.iter().copied().filter(move |&(a, b)| {
+        directions
+            .iter()
+            .all(|&(dx, dy)| (a as i16 * dx as i16 + b as i16 * dy as i16).rem_euclid(3) != 0)
+    })
+}
+pub fn colour((a, b): (u8, u8), p: Position) -> u8 {
+    ((a as u16 * p.x as u16 + b as u16 * p.y as u16) % 3) as u8
+}
+
+/// For each of the `colourings`, the parities of the stones on colours 0 and 1 and on colours
+/// 1 and 2, two bits each. A jump flips the parity of all three colours so this never changes
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PositionClass(pub u8);
+
+impl fmt::Display for PositionClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:b}", self.0)
+    }
+}
+
+/// Why one board can never be played into another
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Unreachable {
+    /// Every jump removes a stone, so the target needs fewer stones
+    TooFewStones { from: usize, to: usize },
+    /// Same number of stones but not the same board
+    Different,
+    PositionClass { from: PositionClass, to: PositionClass },
+}
+
+impl fmt::Display for Unreachable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unreachable::TooFewStones { from, to } => write!(
+                f,
+                "the board has {} stones and jumps only remove stones, it can't get to {}",
+                from, to
+            ),
+            Unreachable::Different => write!(f, "no jumps are made but the boards differ"),
+            Unreachable::PositionClass { from, to } => write!(
+                f,
+                "the board is in position class {} but the target is in {}, jumps never change the class",
+                from, to
+            ),
+        }
+    }
+}
+
 pub type BitSetType = u64;
 pub type BitSet = FixedBitSet<BitSetType>;
+/// Colours are stored one bit plane each, enough for `Geometry::COLOURS` up to 4
+const COLOUR_PLANES: usize = 2;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub struct Board<const SIZE: u8> {
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Board<G: Geometry> {
     dots: BitSet,
+    /// Bits of the colour of each stone, always clear on holes
+    colours: [BitSet; COLOUR_PLANES],
+    geometry: PhantomData<G>,
 }
 
-impl<const SIZE: u8> Board<SIZE> {
+impl<G: Geometry> Board<G> {
+    /// Stones get their `start_colour`
+    fn new(dots: BitSet) -> Self {
+        let board = Board {
+            dots,
+            colours: [BitSet::new(0); COLOUR_PLANES],
+            geometry: PhantomData,
+        };
+        if G::COLOURS > 1 {
+            dots.iter_pos().fold(board, |board, i| {
+                let p = Self::get_pos(i).expect("Stones are on the board");
+                board.set_colour(i, Self::start_colour(p))
+            })
+        } else {
+            board
+        }
+    }
     // Initialize a board with an empty spot at `e`
     pub fn full() -> Self {
-        Board {
-            dots: Self::iter_all()
+        Board::new(
+            Self::iter_all()
                 .map(|p| Self::get_idx(p).expect("Inbounds by construction"))
                 .collect(),
-        }
+        )
     }
     pub fn start(e: Position) -> Self {
-        Board {
-            dots: Self::iter_all()
+        Board::new(
+            Self::iter_all()
                 .filter(|&p: &Position| p != e)
                 .map(|p| Self::get_idx(p).expect("Inbounds by construction"))
                 .collect(),
-        }
+        )
+    }
+    /// Full board apart from the empty spots in `holes`
+    pub fn start_with(holes: impl IntoIterator<Item = Position>) -> Self {
+        let holes = holes.into_iter().collect_vec();
+        Self::iter_all().filter(|p| !holes.contains(p)).collect()
     }
-    pub fn row_len(y: u8) -> u8 {
-        SIZE - y
+    pub fn empty() -> Self {
+        Board::new(BitSet::new(0))
     }
     pub fn at(&self, p: Position) -> Option<bool> {
         Some(
@@ -90,56 +184,108 @@ impl<const SIZE: u8> Board<SIZE> {
         )
     }
     fn valid_pos(p: Position) -> bool {
-        p.y < SIZE && p.x < Self::row_len(p.y)
-    }
-    // Round up to power two so that mul becomes shl and mod becomes bitand, uses more memory
-    const ROW_OFFSET: usize = 1 << (size_of_val(&SIZE) * 8 - SIZE.leading_zeros() as usize);
-    fn get_idx(p: Position) -> Option<usize> {
-        Self::valid_pos(p).as_some((p.x as usize) | ((p.y as usize) * Self::ROW_OFFSET))
-    }
-    fn get_pos(i: impl TryInto<u16>) -> Option<Position> {
-        let i = i.try_into().ok()?;
-        let r: u16 = Self::ROW_OFFSET.try_into().expect("Statically known");
-
-        let p = Position::try_from((i % r, i / r)).ok()?;
-        Self::valid_pos(p).as_some(p)
-    }
-    pub fn row(&self, y: u8) -> Option<BitSet> {
-        (y < SIZE).as_some(()).and_then(|()| {
-            Some(
-                self.dots
-                    .get(Self::get_idx(pos(0, y)).expect("Inbounds by construction")..)
-                    .expect("Bounds checked 1")
-                    .get(..Self::row_len(y) as usize)
-                    .expect("Bounds checked 2"),
-            )
-        })
+        G::valid_pos(p)
+    }
+    pub fn get_idx(p: Position) -> Option<usize> {
+        G::get_idx(p)
+    }
+    fn get_pos(i: usize) -> Option<Position> {
+        G::get_pos(i)
     }
     fn at_index(&self, i: usize) -> Option<bool> {
         self.dots.get(i)
     }
 
+    /// Colour a new stone at `p` gets, stripes across the board
+    pub fn start_colour(p: Position) -> u8 {
+        ((p.x as u16 + 2 * p.y as u16) % G::COLOURS as u16) as u8
+    }
+    fn colour_index(&self, i: usize) -> u8 {
+        self.colours
+            .iter()
+            .enumerate()
+            .map(|(plane, bits)| ((bits.val >> i) as u8 & 1) << plane)
+            .sum()
+    }
+    fn set_colour(mut self, i: usize, colour: u8) -> Self {
+        for (plane, bits) in self.colours.iter_mut().enumerate() {
+            bits.val = bits.val & !(1 << i) | ((colour >> plane) as BitSetType & 1) << i;
+        }
+        self
+    }
+    /// Colour of the stone at `p`, None if there is no stone
+    pub fn colour_at(&self, p: Position) -> Option<u8> {
+        let i = Self::get_idx(p)?;
+        self.at_index(i)?.as_some_from(|| self.colour_index(i))
+    }
+    /// Repaint the stone at `p`, None if there is no stone or no such colour
+    pub fn with_colour(&self, p: Position, colour: u8) -> Option<Self> {
+        let i = Self::get_idx(p)?;
+        (self.at_index(i)? && colour < G::COLOURS).as_some_from(|| self.set_colour(i, colour))
+    }
+    /// Put a stone of `colour` at `p`
+    fn place(mut self, p: Position, colour: u8) -> Option<Self> {
+        let i = Self::get_idx(p)?;
+        self.dots = self.dots.union(BitSet::new(1 << i));
+        Some(self.set_colour(i, colour))
+    }
+    /// Whether the stone at `from` may jump the one at `over`
+    fn captures(&self, from: Position, over: Position) -> bool {
+        match G::CAPTURE {
+            Capture::Any => true,
+            Capture::Different => self.colour_at(from) != self.colour_at(over),
+            Capture::Same => self.colour_at(from) == self.colour_at(over),
+        }
+    }
+
+    fn step(p: Position, (dx, dy): (i8, i8), dist: i16) -> Option<Position> {
+        Position::try_from((p.x as i16 + dx as i16 * dist, p.y as i16 + dy as i16 * dist)).ok()
+    }
     fn neighbours(p: Position, dist: u8) -> impl Iterator<Item = Option<Position>> {
-        lazy_fixed_iter![
-            move || Some((p.x.checked_add(dist)?, p.y)),
-            Some((p.x, p.y.checked_add(dist)?)),
-            Some((p.x.checked_sub(dist)?, p.y.checked_add(dist)?)),
-            Some((p.x.checked_sub(dist)?, p.y)),
-            Some((p.x, p.y.checked_sub(dist)?)),
-            Some((p.x.checked_add(dist)?, p.y.checked_sub(dist)?))
-        ]
-        .map(|p| p.map(Position::from))
+        G::DIRECTIONS
+            .iter()
+            .map(move |&d| Self::step(p, d, dist as i16))
+    }
+    /// Cells next to `p` that are on the board
+    pub fn adjacent(p: Position) -> impl Iterator<Item = Position> {
+        Self::neighbours(p, 1)
+            .flatten()
+            .filter(|&p| Self::valid_pos(p))
+    }
+    /// Every (from, over, to) line a single jump could take, ignoring where the stones are
+    pub fn jumps() -> impl Iterator<Item = (Position, Position, Position)> {
+        Self::iter_all().flat_map(|p| {
+            izip!(Self::neighbours(p, 1), Self::neighbours(p, 2))
+                .filter_map(|(e, t)| e.zip(t))
+                .filter(|&(e, t)| Self::valid_pos(e) && Self::valid_pos(t))
+                .map(move |(e, t)| (p, e, t))
+        })
+    }
+
+    /// (first stone jumped, landing spot) of the jump from `p` along `d`, if there is one
+    fn jump(&self, p: Position, d: (i8, i8)) -> Option<(Position, Position)> {
+        let e = Self::step(p, d, 1)?;
+        if G::LONG_JUMPS {
+            // Over the whole run of stones into the first hole after it
+            let mut k = 1;
+            while self.at(Self::step(p, d, k)?)? {
+                k += 1;
+            }
+            let captures =
+                (1..k).all(|i| Self::step(p, d, i).map_or(false, |q| self.captures(p, q)));
+            (k > 1 && captures).as_some((e, Self::step(p, d, k)?))
+        } else {
+            let t = Self::step(p, d, 2)?;
+            (!self.at(t)? && self.at(e)? && self.captures(p, e)).as_some((e, t))
+        }
     }
 
     pub fn valid_moves(
         &self,
         p: Position,
     ) -> Option<impl Iterator<Item = (Position, Position)> + '_> {
-        self.at(p)?.as_some_from(move || {
-            izip!(Self::neighbours(p, 1), Self::neighbours(p, 2))
-                .filter_map(|(e, t)| e.zip(t))
-                .filter(move |&(e, t)| !self.at(t).unwrap_or(true) && self.at(e).unwrap_or(false))
-        })
+        self.at(p)?
+            .as_some_from(move || G::DIRECTIONS.iter().filter_map(move |&d| self.jump(p, d)))
     }
 
     // from, over, to
@@ -160,124 +306,328 @@ impl<const SIZE: u8> Board<SIZE> {
         } else if self.at(to)? {
             // Space at the ending
             None
-        } else if let Some((i, _)) = Self::neighbours(fr, 2)
-            .into_iter()
-            .find_position(|&x| x.map(|x| x == to).unwrap_or(false))
-        {
-            // Stone to jump over
-            // TODO: check that this optimizes well
-            let p = Self::neighbours(fr, 1)
-                .nth(i)
-                .expect("This index is from an Iterator::find_position")
-                .expect("If the 2 away position is valid then this should be valid");
-            self.at(p).and_then(|b| b.as_some(p))
         } else {
-            None
+            // Stone to jump over
+            G::DIRECTIONS
+                .iter()
+                .filter_map(|&d| self.jump(fr, d))
+                .find(|&(_, t)| t == to)
+                .map(|(e, _)| e)
         }
     }
+    /// The (from, over, to) jump that turns this board into `next`, if there is one
+    pub fn jump_to(&self, next: &Self) -> Option<(Position, Position, Position)> {
+        self.all_valid_moves()
+            .find(|&(from, _, to)| self.apply_move(from, to).as_ref() == Some(next))
+    }
+
+    /// Every jump and board it is made from that leaves this board. Stones jumped over come back
+    /// in every colour the rules allow
+    pub fn predecessors(&self) -> Vec<((Position, Position, Position), Self)> {
+        let longest = if G::LONG_JUMPS { G::ROWS as i16 } else { 1 };
+        let mut found = Vec::new();
+        for to in self.iter_stones() {
+            let colour = self.colour_at(to).expect("Stone");
+            for &d in G::DIRECTIONS {
+                let back = |k: i16| {
+                    Self::step(to, d, -k).filter(|&p| self.at(p) == Some(false))
+                };
+                for k in 1..=longest {
+                    // Every cell between `from` and `to` has to be empty now
+                    let overs = match (1..=k).map(back).collect::<Option<Vec<_>>>() {
+                        Some(overs) => overs,
+                        None => break,
+                    };
+                    let from = match back(k + 1) {
+                        Some(from) => from,
+                        None => continue,
+                    };
+                    let before = self
+                        .with_stone(to, false)
+                        .and_then(|b| b.place(from, colour))
+                        .expect("On the board");
+                    let palettes = iter::repeat(0..G::COLOURS).take(overs.len());
+                    for colours in palettes.multi_cartesian_product() {
+                        let prev = izip!(&overs, colours)
+                            .try_fold(before, |b, (&over, c)| b.place(over, c))
+                            .expect("On the board");
+                        if prev.apply_move(from, to).as_ref() == Some(self) {
+                            found.push(((from, overs[k as usize - 1], to), prev));
+                        }
+                    }
+                }
+            }
+        }
+        found
+    }
 
     pub fn iter_all() -> impl Iterator<Item = Position> {
-        (0..SIZE).flat_map(|y| (0..Self::row_len(y)).map(move |x| pos(x, y)))
+        Self::iter_shape().filter(|&p| !G::blocked(p))
+    }
+    /// Cells that can never be used
+    pub fn iter_blocked() -> impl Iterator<Item = Position> {
+        Self::iter_shape().filter(|&p| G::blocked(p))
+    }
+    fn iter_shape() -> impl Iterator<Item = Position> {
+        (0..G::ROWS).flat_map(|y| G::row(y).map(move |x| pos(x, y)))
     }
     pub fn iter_stones(&self) -> impl Iterator<Item = Position> {
         self.dots.iter_pos().map(Self::get_pos).flatten()
         // .map(|x| x.expect("Must be a valid position"))
     }
+    pub fn iter_holes(&self) -> impl Iterator<Item = Position> + '_ {
+        Self::iter_all().filter(move |&p| !self.at(p).expect("Must be valid"))
+    }
     pub fn iter(&self) -> impl Iterator<Item = (Position, bool)> + '_ {
         Self::iter_all().map(move |p| (p, self.at(p).expect("Must be valid")))
     }
 
-    pub fn map(&self, map: impl FnMut(Position) -> Position) -> Option<Board<SIZE>> {
-        self.iter_stones().map(map).collect()
-    }
-    pub fn filter_map(&self, map: impl FnMut(Position) -> Option<Position>) -> Option<Board<SIZE>> {
-        self.iter_stones().filter_map(map).collect()
+    /// Stones with their colours
+    pub fn iter_colours(&self) -> impl Iterator<Item = (Position, u8)> + '_ {
+        self.dots
+            .iter_pos()
+            .filter_map(move |i| Some((Self::get_pos(i)?, self.colour_index(i))))
     }
-    pub fn filter(&self, p: impl FnMut(&Position) -> bool) -> Board<SIZE> {
-        self.iter_stones().filter(p).collect()
+
+    /// Move every stone, keeping its colour
+    pub fn map(&self, mut map: impl FnMut(Position) -> Position) -> Option<Board<G>> {
+        self.iter_colours()
+            .try_fold(Self::empty(), |board, (p, colour)| board.place(map(p), colour))
+    }
+    pub fn filter_map(&self, mut map: impl FnMut(Position) -> Option<Position>) -> Option<Board<G>> {
+        self.iter_colours()
+            .filter_map(|(p, colour)| Some((map(p)?, colour)))
+            .try_fold(Self::empty(), |board, (p, colour)| board.place(p, colour))
+    }
+    pub fn filter(&self, mut p: impl FnMut(&Position) -> bool) -> Board<G> {
+        self.filter_map(|q| p(&q).as_some(q))
+            .expect("Stones are on the board")
+    }
+    /// Symmetries of the geometry that also keep the set of jump directions and the blocked cells
+    pub fn symmetries() -> impl Iterator<Item = Symmetry> {
+        G::SYMMETRIES.iter().copied().filter(|&sym| {
+            !G::RESTRICTED
+                || (Self::keeps_directions(sym) && Self::iter_blocked().all(|p| G::blocked(sym(p))))
+        })
     }
-    fn flip(&self) -> Self {
-        // self.iter_pos().map(|p| pos(SIZE - 1 - p.x, p.y)).collect()
-        let mut dots: BitSetType = 0;
-        for row in 0..SIZE {
-            let rowbits = self.row(row as u8).expect("By definition");
-            dots |= rowbits.revn(Self::row_len(row as u8).into()).val
-                << Self::get_idx(pos(0, row as u8)).expect("Inbounds by construction");
-        }
-        Board {
-            dots: BitSet::new(dots),
-        }
+    fn keeps_directions(sym: Symmetry) -> bool {
+        G::DIRECTIONS.iter().all(|&d| {
+            Self::iter_all()
+                .find_map(|p| Some((p, Self::step(p, d, 1).filter(|&q| Self::valid_pos(q))?)))
+                .map(|(p, q)| {
+                    let (a, b) = (sym(p), sym(q));
+                    let image = (b.x as i16 - a.x as i16, b.y as i16 - a.y as i16);
+                    G::DIRECTIONS.contains(&(image.0 as i8, image.1 as i8))
+                })
+                .unwrap_or(true)
+        })
     }
-    fn rotate_right(&self) -> Self {
-        self.iter_stones()
-            .map(|p| pos(p.y, SIZE - 1 - p.x - p.y))
-            .collect()
-    }
-    fn rotate_left(&self) -> Self {
-        self.iter_stones()
-            .map(|p| pos(SIZE - 1 - p.y - p.x, p.x))
-            .collect()
-    }
-    fn rotate_right_flip(&self) -> Self {
-        self.iter_stones()
-            .map(|p| pos(p.y, SIZE - 1 - p.y))
-            .map(|p| pos(SIZE - 1 - p.x, p.y))
-            .collect()
-    }
-    fn rotate_left_flip(&self) -> Self {
-        self.iter_stones()
-            .map(|p| pos(SIZE - 1 - p.y, p.x))
-            .map(|p| pos(SIZE - 1 - p.x, p.y))
-            .collect()
-    }
-    fn all_variants(&self) -> impl Iterator<Item = Board<SIZE>> {
+    fn all_variants(&self) -> impl Iterator<Item = Board<G>> {
         let base = *self;
-        let r = self.rotate_right();
-        let l = self.rotate_left();
-        lazy_fixed_iter![move || base, base.flip(), r, l, r.flip(), l.flip()]
+        Self::symmetries().map(move |sym| {
+            base.map(sym)
+                .expect("Symmetries map the board onto itself")
+        })
     }
     pub fn canonicalize(&self) -> Self {
         // The chose of canonical value is kinda arbitrary, maybe a more relevant method would be better
         // But this should be pretty fast
         self.all_variants()
-            .min_by_key(|board| board.dots.val)
+            .min_by_key(|board| (board.dots.val, board.colours))
             .expect("Array is non empty")
     }
 
     pub fn apply_move(&self, from: Position, to: Position) -> Option<Self> {
         let elim = self.valid_move(from, to)?;
-        Some(
-            self.filter_map(|old| {
-                if old == from {
-                    Some(to)
-                } else if old == elim {
-                    None
-                } else {
-                    Some(old)
-                }
-            })
-            .expect("Valid board because both pickup and stone are valid"),
-        )
+        // Every stone between the first one jumped and the landing spot
+        let d = ((elim.x as i16 - from.x as i16) as i8, (elim.y as i16 - from.y as i16) as i8);
+        let over: SmallVec<[Position; 4]> = iter::successors(Some(elim), |&p| Self::step(p, d, 1))
+            .take_while(|&p| p != to)
+            .collect();
+        let colour = self.colour_at(from)?;
+        over.iter()
+            .chain(iter::once(&from))
+            .try_fold(*self, |board, &p| board.with_stone(p, false))?
+            .place(to, colour)
+    }
+
+    /// Place or remove the stone at `p`, None if `p` is off the board.
+    /// A new stone gets its `start_colour`
+    pub fn with_stone(&self, p: Position, stone: bool) -> Option<Self> {
+        let i = Self::get_idx(p)?;
+        if self.at_index(i)? == stone {
+            Some(*self)
+        } else if stone {
+            self.place(p, Self::start_colour(p))
+        } else {
+            let mut board = self.set_colour(i, 0);
+            board.dots = BitSet::new(self.dots.val & !(1 << i));
+            Some(board)
+        }
     }
 
     pub fn count(&self) -> usize {
         self.dots.len()
     }
+
+    /// A board with only the stone at `p`
+    pub fn single(p: Position) -> Self {
+        iter::once(p).collect()
+    }
+    pub fn position_class(&self) -> PositionClass {
+        if G::LONG_JUMPS {
+            // A long jump removes any number of stones, no parity survives that
+            return PositionClass(0);
+        }
+        PositionClass(
+            colourings(G::DIRECTIONS)
+                .enumerate()
+                .fold(0, |class, (i, c)| {
+                    let mut n = [0usize; 3];
+                    self.iter_stones().for_each(|p| n[colour(c, p) as usize] += 1);
+                    class
+                        | (((n[0] + n[1]) % 2) as u8) << (2 * i)
+                        | (((n[1] + n[2]) % 2) as u8) << (2 * i + 1)
+                }),
+        )
+    }
+    /// Ok if no invariant rules out playing from this board to `target`, this is a
+    /// necessary condition only and doesn't search
+    pub fn reachability(&self, target: &Self) -> Result<(), Unreachable> {
+        let (from, to) = (self.count(), target.count());
+        if from < to {
+            Err(Unreachable::TooFewStones { from, to })
+        } else if from == to && self != target {
+            Err(Unreachable::Different)
+        } else if self.position_class() != target.position_class() {
+            Err(Unreachable::PositionClass {
+                from: self.position_class(),
+                to: target.position_class(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+    pub fn may_reach(&self, target: &Self) -> bool {
+        self.reachability(target).is_ok()
+    }
+    /// Holes where this board could possibly finish with a single stone
+    pub fn possible_finishes(&self) -> impl Iterator<Item = Position> + '_ {
+        Self::iter_all().filter(move |&p| self.may_reach(&Self::single(p)))
+    }
 }
 
-impl<const SIZE: u8> FromIterator<Position> for Board<SIZE> {
+/// Stones by colour in the text notation, holes are `.` and blocked cells `#`
+const STONE_CHARS: [char; 4] = ['o', 'x', '+', '*'];
+
+/// One line per row, cells spaced out as they are drawn
+impl<G: Geometry> fmt::Display for Board<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let column = |p: Position| (G::layout(p).0 * 2.).round() as i32;
+        let left = Self::iter_shape().map(column).min().unwrap_or(0);
+        for y in 0..G::ROWS {
+            let mut line = String::new();
+            for x in G::row(y) {
+                let p = pos(x, y);
+                let cell = if G::blocked(p) {
+                    '#'
+                } else {
+                    self.colour_at(p)
+                        .map_or('.', |colour| STONE_CHARS[colour as usize])
+                };
+                let pad = (column(p) - left) as usize;
+                line.extend(iter::repeat(' ').take(pad.saturating_sub(line.len())));
+                line.push(cell);
+            }
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Why a string isn't a board in the text notation
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParseBoardError {
+    Rows { expected: u8, found: usize },
+    RowLength { row: u8, expected: usize, found: usize },
+    /// Not a stone of one of the board's colours, a hole or `#` on a blocked cell
+    Cell { row: u8, column: usize, found: char },
+}
+
+impl fmt::Display for ParseBoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseBoardError::Rows { expected, found } => {
+                write!(f, "expected {} rows, found {}", expected, found)
+            }
+            ParseBoardError::RowLength {
+                row,
+                expected,
+                found,
+            } => write!(f, "row {} should have {} cells, found {}", row, expected, found),
+            ParseBoardError::Cell { row, column, found } => {
+                write!(f, "row {} cell {} can't be '{}'", row, column, found)
+            }
+        }
+    }
+}
+
+/// Reads what `Display` writes, spacing doesn't matter and blank lines are skipped
+impl<G: Geometry> FromStr for Board<G> {
+    type Err = ParseBoardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines = s.lines().filter(|l| !l.trim().is_empty()).collect_vec();
+        if lines.len() != G::ROWS as usize {
+            return Err(ParseBoardError::Rows {
+                expected: G::ROWS,
+                found: lines.len(),
+            });
+        }
+        izip!(0.., lines).try_fold(Board::empty(), |board, (y, line)| {
+            let cells = line.chars().filter(|c| !c.is_whitespace()).collect_vec();
+            let row = G::row(y);
+            if cells.len() != row.len() {
+                return Err(ParseBoardError::RowLength {
+                    row: y,
+                    expected: row.len(),
+                    found: cells.len(),
+                });
+            }
+            izip!(row, cells)
+                .enumerate()
+                .try_fold(board, |board, (column, (x, c))| {
+                    let p = pos(x, y);
+                    let colour = STONE_CHARS.iter().position(|&s| s == c);
+                    let cell = match (G::blocked(p), c, colour) {
+                        (true, '#', _) | (false, '.', _) => Some(board),
+                        (false, _, Some(colour)) => board
+                            .with_stone(p, true)
+                            .and_then(|b| b.with_colour(p, colour as u8)),
+                        _ => None,
+                    };
+                    cell.ok_or(ParseBoardError::Cell {
+                        row: y,
+                        column,
+                        found: c,
+                    })
+                })
+        })
+    }
+}
+
+impl<G: Geometry> FromIterator<Position> for Board<G> {
     fn from_iter<T: IntoIterator<Item = Position>>(iter: T) -> Self {
-        Option::<Board<SIZE>>::from_iter(iter).unwrap()
+        Option::<Board<G>>::from_iter(iter).unwrap()
     }
 }
 
-impl<const SIZE: u8> FromIterator<Position> for Option<Board<SIZE>> {
+impl<G: Geometry> FromIterator<Position> for Option<Board<G>> {
     fn from_iter<T: IntoIterator<Item = Position>>(iter: T) -> Self {
-        Some(Board {
-            dots: iter
-                .into_iter()
-                .map(Board::<SIZE>::get_idx)
+        Some(Board::new(
+            iter.into_iter()
+                .map(Board::<G>::get_idx)
                 .collect::<Option<_>>()?,
-        })
+        ))
     }
 }
@@ -0,0 +1,305 @@
+use std::io::{self, BufRead, Write};
+
+use itertools::Itertools;
+
+use crate::{
+    ai::{Opponent, Strength},
+    board::{Board, Position},
+    commutation::foata_text,
+    daily::{self, Challenge, Date},
+    geometry::Geometry,
+    goal::Goal,
+    puzzle::{generate, Rater},
+    reach,
+    score::{self, Session},
+    solutions::{solution_text, Dedup, Solutions},
+    solver::Solver,
+    starts,
+    strategy::{distribution, Greedy, Perfect, Random},
+    versus::Play,
+};
+
+/// Value of `--name=value`, empty for a bare `--name`
+pub fn flag(name: &str) -> Option<String> {
+    std::env::args().skip(1).find_map(|arg| {
+        let rest = arg.strip_prefix("--")?.strip_prefix(name)?;
+        if rest.is_empty() {
+            Some(String::new())
+        } else {
+            rest.strip_prefix('=').map(str::to_owned)
+        }
+    })
+}
+/// The `n`th argument that isn't a flag
+pub fn arg(n: usize) -> Option<String> {
+    std::env::args()
+        .skip(1)
+        .filter(|arg| !arg.starts_with("--"))
+        .nth(n)
+}
+/// The board and rules picked on the command line
+pub fn board_name() -> String {
+    let name = std::env::args()
+        .skip(1)
+        .filter(|arg| !arg.starts_with("--"))
+        .join(" ");
+    if name.is_empty() {
+        "triangle".to_owned()
+    } else {
+        name
+    }
+}
+/// A single stone in a corner, a cell no jump goes over
+fn corner_finish<G: Geometry>(board: &Board<G>) -> bool {
+    board.count() == 1
+        && board
+            .iter_stones()
+            .all(|p| Board::<G>::jumps().all(|(_, over, _)| over != p))
+}
+/// `x,y` to finish on that hole, `start` where the only hole of `start` is, `corner` in any
+/// corner, or else the name of a file with the board to finish on
+pub fn parse_goal<G: Geometry>(s: &str, start: &Board<G>) -> Option<Goal<G>> {
+    match s {
+        "start" => Goal::complement(start),
+        "corner" => Some(Goal::Custom(corner_finish)),
+        _ => match Position::parse(s) {
+            // Only holes on the board
+            Some(hole) => Board::<G>::get_idx(hole).map(|_| Goal::Hole(hole)),
+            None => read_board(s).map(Goal::Pattern),
+        },
+    }
+}
+/// The goal given by `--finish`, any single stone without it. None if it can't be read
+pub fn goal_flag<G: Geometry>(start: &Board<G>) -> Option<Goal<G>> {
+    match flag("finish") {
+        Some(finish) => parse_goal(&finish, start).or_else(|| {
+            println!("--finish=x,y, --finish=start, --finish=corner or --finish=board.txt");
+            None
+        }),
+        None => Some(Goal::AnyOne),
+    }
+}
+
+/// Play the two player game against the computer in the terminal
+pub fn versus<G: Geometry>(start: Board<G>, play: Play, strength: Strength, human_first: bool) {
+    let mut opponent = Opponent::new(play, strength);
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut board = start;
+    println!("Two players, {}, computer plays {:?}", play, strength);
+    for turn in 0.. {
+        let human = (turn % 2 == 0) == human_first;
+        println!();
+        print!("{}", board);
+        let jumps = board.all_valid_moves().collect_vec();
+        if jumps.is_empty() {
+            let human_wins = human == (play == Play::Misere);
+            println!(
+                "{} can't jump, {}",
+                if human { "You" } else { "The computer" },
+                if human_wins { "you win" } else { "the computer wins" }
+            );
+            return;
+        }
+        let (from, _, to) = if human {
+            for (i, (from, _, to)) in jumps.iter().enumerate() {
+                println!("{}: ({}, {}) to ({}, {})", i, from.x, from.y, to.x, to.y);
+            }
+            loop {
+                print!("Your jump: ");
+                io::stdout().flush().expect("Can write to stdout");
+                let line = match lines.next() {
+                    Some(Ok(line)) => line,
+                    _ => return,
+                };
+                match line.trim().parse::<usize>().ok().and_then(|i| jumps.get(i)) {
+                    Some(&jump) => break jump,
+                    None => println!("Pick a number from 0 to {}", jumps.len() - 1),
+                }
+            }
+        } else {
+            let decision = opponent.choose(&board).expect("There is a jump");
+            let (from, _, to) = decision.jump;
+            print!("Computer jumps ({}, {}) to ({}, {})", from.x, from.y, to.x, to.y);
+            match decision.outcome() {
+                Some(outcome) => println!(", a {} with perfect play", outcome),
+                None => println!(", searched {} jumps ahead", decision.depth),
+            }
+            decision.jump
+        };
+        board = board.apply_move(from, to).expect("Valid by construction");
+    }
+}
+
+/// Stones left by each strategy over `games` games from `start`
+pub fn strategies<G: Geometry>(start: Board<G>, games: usize) {
+    print!("{}", start);
+    println!("Random");
+    println!("{}", distribution(&mut Random::new(0), start, games));
+    println!("Greedy, central");
+    println!("{}", distribution(&mut Greedy::central(0), start, games));
+    println!("Greedy, connected");
+    println!("{}", distribution(&mut Greedy::connected(0), start, games));
+    // Perfect play always makes the same jumps
+    println!("Perfect");
+    println!("{}", distribution(&mut Perfect::default(), start, 1));
+}
+
+/// Print a batch of puzzles in the board notation, hardest first
+pub fn puzzles<G: Geometry>(stones: usize, count: usize, max_solutions: u64, seed: u64) {
+    let puzzles = generate::<G>(stones, count, max_solutions, seed);
+    if puzzles.len() < count {
+        println!(
+            "Only found {} puzzles with {} stones and at most {} solutions",
+            puzzles.len(),
+            stones,
+            max_solutions
+        );
+    }
+    for (i, puzzle) in puzzles.iter().enumerate() {
+        println!();
+        print!("Puzzle {}: {}", i + 1, puzzle);
+    }
+}
+
+/// The board written in the file at `path`, says what is wrong if there isn't one
+pub fn read_board<G: Geometry>(path: &str) -> Option<Board<G>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| println!("Couldn't read {}: {}", path, e))
+        .ok()?;
+    text.parse()
+        .map_err(|e| println!("{} isn't a board: {}", path, e))
+        .ok()
+}
+
+/// Rate the board written in the file at `path`
+pub fn rate<G: Geometry>(path: &str) {
+    if let Some(board) = read_board::<G>(path) {
+        match Rater::default().rate(&board) {
+            Some(puzzle) => print!("{}", puzzle),
+            None => println!("{}can't be played down to one stone", board),
+        }
+    }
+}
+
+/// How to play the board in the file at `from` into the one at `to`
+pub fn reach<G: Geometry>(from: &str, to: &str) {
+    let (a, b) = match (read_board::<G>(from), read_board::<G>(to)) {
+        (Some(a), Some(b)) => (a, b),
+        _ => return,
+    };
+    match reach::diff(&a, &b) {
+        Ok(jumps) => println!("{} jumps: {}", jumps.len(), solution_text(&jumps)),
+        Err(why) => println!("Unreachable, {}", why),
+    }
+}
+
+/// Jumps made in a record of boards in the file at `path`
+pub fn record<G: Geometry>(path: &str) {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => {
+            println!("Couldn't read {}: {}", path, e);
+            return;
+        }
+    };
+    let boards = match reach::parse_record::<G>(&text) {
+        Ok(boards) => boards,
+        Err(e) => {
+            println!("{} isn't a record of boards: {}", path, e);
+            return;
+        }
+    };
+    match reach::replay(&boards) {
+        Ok(jumps) => println!("{} jumps: {}", jumps.len(), solution_text(&jumps)),
+        Err((i, why)) => println!("Board {} doesn't follow from the one before, {}", i + 1, why),
+    }
+}
+
+/// Which of `starts` can be solved and where each finishes
+pub fn starts<G: Geometry>(starts: impl IntoIterator<Item = Board<G>>) {
+    let reports = starts::report(starts);
+    for report in &reports {
+        println!("{}", report);
+    }
+    let solvable = reports.iter().filter(|report| report.solvable()).count();
+    println!("{} of {} starts solvable", solvable, reports.len());
+}
+
+/// A solution from `board` that plays packages where they fit
+pub fn macro_solution<G: Geometry>(board: Board<G>, goal: Goal<G>) {
+    match Solver::new(goal).macro_solution(&board) {
+        Some(steps) => {
+            println!("{} moves", steps.len());
+            for step in steps {
+                println!("{}", step);
+            }
+        }
+        None => println!("{:?} can't be reached", goal),
+    }
+}
+
+/// The challenge of `date` and the results logged for it
+pub fn daily<G: Geometry>(date: Date) {
+    let challenge = Challenge::<G>::new(date);
+    println!("Daily challenge {}", challenge);
+    print!("{}", challenge.start);
+    let results = daily::results(&challenge);
+    if results.is_empty() {
+        println!("Nobody has played it here yet");
+    }
+    for (i, entry) in results.iter().enumerate() {
+        println!(
+            "{:>3}. {:<12} {:>3} stones{} in {}s",
+            i + 1,
+            entry.player,
+            entry.stones,
+            if entry.solved { ", solved" } else { "" },
+            entry.seconds
+        );
+    }
+}
+
+/// One line about a scored game
+pub fn session(session: &Session) -> String {
+    let start = session.start.iter().map(|p| (p.x, p.y)).collect_vec();
+    format!(
+        "{} from {:?}: {} stones{}, {} hints, {} undos, {}s, {} points, {}",
+        session.board,
+        start,
+        session.stones,
+        if session.complement { " on the start hole" } else { "" },
+        session.hints,
+        session.undos,
+        session.seconds,
+        session.score(),
+        session.rating()
+    )
+}
+
+/// Best score for every board and start played
+pub fn history() {
+    let history = score::history();
+    println!("{} games played", history.len());
+    for best in score::personal_bests(&history) {
+        println!("{}", session(best));
+    }
+}
+
+/// Print up to `limit` solutions from `start`, one line each, then how many there were.
+/// Without commuting jumps they are shown as steps of jumps that could be made together
+pub fn enumerate<G: Geometry>(start: Board<G>, goal: Goal<G>, dedup: Dedup, limit: Option<usize>) {
+    if !goal.may_reach(&start) {
+        println!("{:?} is out of reach", goal);
+    }
+    let mut count = 0;
+    for solution in Solutions::new(start, goal, dedup).take(limit.unwrap_or(usize::MAX)) {
+        if dedup.commuting {
+            println!("{}", foata_text(&solution));
+        } else {
+            println!("{}", solution_text(&solution));
+        }
+        count += 1;
+    }
+    println!("{} solutions", count);
+}
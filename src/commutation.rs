@@ -0,0 +1,75 @@
+use itertools::Itertools;
+
+use crate::{board::Position, solutions::solution_text, strategy::Jump};
+
+/// Every cell a jump empties or fills
+pub fn cells((from, _, to): Jump) -> Vec<Position> {
+    let (dx, dy) = (to.x as i16 - from.x as i16, to.y as i16 - from.y as i16);
+    let steps = num::integer::gcd(dx, dy).max(1);
+    (0..=steps)
+        .map(|i| {
+            Position::new(
+                (from.x as i16 + dx / steps * i) as u8,
+                (from.y as i16 + dy / steps * i) as u8,
+            )
+        })
+        .collect()
+}
+
+/// Jumps touching different cells can be made in either order and leave the same board
+pub fn commute(a: Jump, b: Jump) -> bool {
+    let cells = cells(a);
+    self::cells(b).iter().all(|p| !cells.contains(p))
+}
+
+/// The smallest reordering of `jumps` by swapping commuting neighbours
+pub fn lex_normal_form(jumps: &[Jump]) -> Vec<Jump> {
+    let mut rest = jumps.to_vec();
+    let mut normal = Vec::with_capacity(jumps.len());
+    while !rest.is_empty() {
+        // Jumps that could be moved to the front
+        let (i, _) = (0..rest.len())
+            .filter(|&i| rest[..i].iter().all(|&a| commute(a, rest[i])))
+            .map(|i| (i, rest[i]))
+            .min_by_key(|&(_, jump)| jump)
+            .expect("The first jump can always go first");
+        normal.push(rest.remove(i));
+    }
+    normal
+}
+
+/// Whether `path` followed by `jump` is still in lexicographic normal form, given `path` is:
+/// `jump` can't be moved in front of a bigger jump it commutes with
+pub fn stays_normal(path: &[Jump], jump: Jump) -> bool {
+    path.iter()
+        .rev()
+        .take_while(|&&a| commute(a, jump))
+        .all(|&a| a < jump)
+}
+
+/// Jumps grouped into steps that could all be made at once, as early as possible: every jump
+/// needs one of the step before it to have been made first. Each step is sorted
+pub fn foata_normal_form(jumps: &[Jump]) -> Vec<Vec<Jump>> {
+    let mut steps = Vec::<Vec<Jump>>::new();
+    for &jump in jumps {
+        // One after the last step with a jump it has to wait for
+        let step = steps
+            .iter()
+            .rposition(|step| step.iter().any(|&a| !commute(a, jump)))
+            .map_or(0, |i| i + 1);
+        if step == steps.len() {
+            steps.push(Vec::new());
+        }
+        steps[step].push(jump);
+    }
+    steps.iter_mut().for_each(|step| step.sort());
+    steps
+}
+
+/// Steps split by `|`, jumps in a step as `x,y-x,y`
+pub fn foata_text(jumps: &[Jump]) -> String {
+    foata_normal_form(jumps)
+        .iter()
+        .map(|step| solution_text(step))
+        .join(" | ")
+}
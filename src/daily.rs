@@ -0,0 +1,224 @@
+use std::{
+    fmt,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    str::FromStr,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use boolinator::Boolinator;
+use itertools::Itertools;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{board::Board, geometry::Geometry, goal::Goal, solver::Solver};
+
+/// Results of every daily challenge played here, one line per game
+pub const LOG_FILE: &str = "daily.txt";
+
+/// A day in the UTC calendar
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    pub fn today() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Clock is after 1970")
+            .as_secs();
+        Self::from_days((secs / 86_400) as i64)
+    }
+    /// Days since 1970-01-01 to a date, Howard Hinnant's `civil_from_days`
+    fn from_days(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        Self {
+            year: (yoe + era * 400 + (month <= 2) as i64) as i32,
+            month: month as u8,
+            day: day as u8,
+        }
+    }
+    /// Same for everyone on the same day
+    fn seed(&self) -> u64 {
+        self.year as u64 * 10_000 + self.month as u64 * 100 + self.day as u64
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// `YYYY-MM-DD`
+impl FromStr for Date {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (year, month, day) = s.trim().split('-').collect_tuple().ok_or(())?;
+        let date = Self {
+            year: year.parse().map_err(|_| ())?,
+            month: month.parse().map_err(|_| ())?,
+            day: day.parse().map_err(|_| ())?,
+        };
+        ((1..=12).contains(&date.month) && (1..=31).contains(&date.day))
+            .as_some(date)
+            .ok_or(())
+    }
+}
+
+/// The start and goal everyone plays on one day
+#[derive(Debug, Copy, Clone)]
+pub struct Challenge<G: Geometry> {
+    pub date: Date,
+    pub start: Board<G>,
+    pub goal: Goal<G>,
+}
+
+impl<G: Geometry> Challenge<G> {
+    /// A random start hole and a random hole its last stone can finish on, both from the date.
+    /// Checked with the solver, so it can always be done
+    pub fn new(date: Date) -> Self {
+        let mut rng = StdRng::seed_from_u64(date.seed());
+        let mut holes = Board::<G>::iter_all().collect_vec();
+        holes.shuffle(&mut rng);
+        for &hole in &holes {
+            let start = Board::start(hole);
+            let mut finishes = start.possible_finishes().collect_vec();
+            finishes.shuffle(&mut rng);
+            if let Some(finish) = finishes
+                .into_iter()
+                .find(|&finish| Solver::new(Goal::Hole(finish)).solvable(&start))
+            {
+                return Self {
+                    date,
+                    start,
+                    goal: Goal::Hole(finish),
+                };
+            }
+        }
+        // No start can finish on a single stone, leave as few as possible
+        Self {
+            date,
+            start: Board::start(holes[0]),
+            goal: Goal::AnyOne,
+        }
+    }
+}
+
+/// Also names the challenge in the results log
+impl<G: Geometry> fmt::Display for Challenge<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let holes = self.start.iter_holes().map(|p| (p.x, p.y)).collect_vec();
+        write!(f, "{}: start without {:?}, ", self.date, holes)?;
+        match self.goal {
+            Goal::Hole(p) => write!(f, "finish on ({}, {})", p.x, p.y),
+            _ => write!(f, "finish with as few stones as possible"),
+        }
+    }
+}
+
+/// One finished game of a challenge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub challenge: String,
+    pub player: String,
+    pub solved: bool,
+    pub stones: usize,
+    pub seconds: u64,
+}
+
+/// Tab separated, a line of the results log
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}",
+            self.challenge, self.player, self.solved, self.stones, self.seconds
+        )
+    }
+}
+
+impl FromStr for Entry {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (challenge, player, solved, stones, seconds) =
+            s.split('\t').collect_tuple().ok_or(())?;
+        Ok(Self {
+            challenge: challenge.to_owned(),
+            player: player.to_owned(),
+            solved: solved.parse().map_err(|_| ())?,
+            stones: stones.parse().map_err(|_| ())?,
+            seconds: seconds.parse().map_err(|_| ())?,
+        })
+    }
+}
+
+/// A challenge being played, recorded once when no jump is left
+#[derive(Debug, Clone)]
+pub struct Attempt<G: Geometry> {
+    pub challenge: Challenge<G>,
+    started: Instant,
+    finished: bool,
+}
+
+impl<G: Geometry> Attempt<G> {
+    pub fn new(challenge: Challenge<G>) -> Self {
+        Self {
+            challenge,
+            started: Instant::now(),
+            finished: false,
+        }
+    }
+    /// The result if `board` ends the game, only the first time
+    pub fn finish(&mut self, board: &Board<G>) -> Option<Entry> {
+        if self.finished || board.all_valid_moves().next().is_some() {
+            return None;
+        }
+        self.finished = true;
+        Some(Entry {
+            challenge: self.challenge.to_string(),
+            player: player(),
+            solved: self.challenge.goal.reached(board),
+            stones: board.count(),
+            seconds: self.started.elapsed().as_secs(),
+        })
+    }
+}
+
+fn player() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "anonymous".to_owned())
+}
+
+/// Append to the results log
+pub fn record(entry: &Entry) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(LOG_FILE)?;
+    writeln!(file, "{}", entry)
+}
+
+/// Results of `challenge` in the log, best first: solved, then fewest stones, then fastest
+pub fn results<G: Geometry>(challenge: &Challenge<G>) -> Vec<Entry> {
+    let name = challenge.to_string();
+    fs::read_to_string(LOG_FILE)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.parse::<Entry>().ok())
+        .filter(|entry| entry.challenge == name)
+        .sorted_by_key(|entry| (!entry.solved, entry.stones, entry.seconds))
+        .collect()
+}
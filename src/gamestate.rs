@@ -1,14 +1,20 @@
-use std::{collections::VecDeque, default::default};
+use std::{collections::VecDeque, default::default, iter};
 
-use crate::board::{Board, Position};
+use crate::{
+    board::{Board, Position},
+    geometry::Geometry,
+    goal::Goal,
+    pagoda::Pagodas,
+    strategy::Jump,
+};
 use boolinator::Boolinator;
 use itertools::Itertools;
 use smallvec::{smallvec, SmallVec};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct GameTreeNode<const SIZE: u8> {
+pub struct GameTreeNode<G: Geometry> {
     // State of the board
-    pub board: Board<SIZE>,
+    pub board: Board<G>,
     /// Index of parent state
     /// TODO: make into a SmallVec
     pub parent: usize,
@@ -16,29 +22,33 @@ pub struct GameTreeNode<const SIZE: u8> {
     pub children: SmallVec<[usize; 2]>,
 }
 
-impl<const SIZE: u8> GameTreeNode<SIZE> {
-    pub fn parent<'a>(&'a self, p: &'a GameTree<SIZE>) -> Option<&'a GameTreeNode<SIZE>> {
+impl<G: Geometry> GameTreeNode<G> {
+    pub fn parent<'a>(&'a self, p: &'a GameTree<G>) -> Option<&'a GameTreeNode<G>> {
         p.states.get(self.parent)
     }
     pub fn children<'a>(
         &'a self,
-        p: &'a GameTree<SIZE>,
-    ) -> impl Iterator<Item = &'a GameTreeNode<SIZE>> + 'a {
+        p: &'a GameTree<G>,
+    ) -> impl Iterator<Item = &'a GameTreeNode<G>> + 'a {
         self.children.iter().filter_map(move |&i| p.states.get(i))
     }
-    pub fn solvable<'a>(&'a self, p: &'a GameTree<SIZE>) -> impl Iterator<Item = usize> + 'a {
+    pub fn solvable<'a>(
+        &'a self,
+        p: &'a GameTree<G>,
+        goal: &'a Goal<G>,
+    ) -> impl Iterator<Item = usize> + 'a {
         self.children
             .iter()
             .copied()
             .filter(move |&ch| {
                 let ch = p.get(ch).expect("Child exists");
                 // ch.board.count() == 1 || ch.solvable(p).next().is_some()
-                ch.solvable(p).next().is_some()
+                ch.solvable(p, goal).next().is_some()
             })
-            .chain((self.board.count() == 1).as_some(usize::MAX))
+            .chain(goal.reached(&self.board).as_some(usize::MAX))
     }
-    pub fn num_solutions<'a>(&'a self, p: &'a GameTree<SIZE>) -> usize {
-        if self.board.count() == 1 {
+    pub fn num_solutions<'a>(&'a self, p: &'a GameTree<G>, goal: &Goal<G>) -> usize {
+        if goal.reached(&self.board) {
             1
         } else {
             self.children
@@ -47,7 +57,7 @@ impl<const SIZE: u8> GameTreeNode<SIZE> {
                 .map(move |ch| {
                     let ch = p.get(ch).expect("Child exists");
                     // ch.board.count() == 1 || ch.solvable(p).next().is_some()
-                    ch.num_solutions(p)
+                    ch.num_solutions(p, goal)
                 })
                 .sum::<usize>()
         }
@@ -55,20 +65,20 @@ impl<const SIZE: u8> GameTreeNode<SIZE> {
 }
 
 #[derive(Debug)]
-pub struct GameTree<const SIZE: u8> {
+pub struct GameTree<G: Geometry> {
     /// Tree of game states (maybe a DAG in the future)
-    states: Vec<GameTreeNode<SIZE>>,
+    states: Vec<GameTreeNode<G>>,
 }
 
-impl<const SIZE: u8> Default for GameTree<SIZE> {
+impl<G: Geometry> Default for GameTree<G> {
     fn default() -> Self {
         Self { states: Vec::new() }
     }
 }
 
-impl<const SIZE: u8> GameTree<SIZE> {
-    // pub type Board = board::Board<SIZE>;
-    pub fn start(board: Board<SIZE>) -> GameTree<SIZE> {
+impl<G: Geometry> GameTree<G> {
+    // pub type Board = board::Board<G>;
+    pub fn start(board: Board<G>) -> GameTree<G> {
         Self {
             states: vec![GameTreeNode {
                 board,
@@ -77,7 +87,7 @@ impl<const SIZE: u8> GameTree<SIZE> {
             }],
         }
     }
-    pub fn push(&mut self, after: usize, board: Board<SIZE>) -> (bool, usize) {
+    pub fn push(&mut self, after: usize, board: Board<G>) -> (bool, usize) {
         // TODO: canonicalize here?
         let children = self.states[after].children.iter();
         if let Some((existingch, node)) = children
@@ -109,20 +119,34 @@ impl<const SIZE: u8> GameTree<SIZE> {
             (ex, idx)
         }
     }
-    pub fn get(&self, i: usize) -> Option<&GameTreeNode<SIZE>> {
+    pub fn get(&self, i: usize) -> Option<&GameTreeNode<G>> {
         self.states.get(i)
     }
-    pub fn get_mut(&mut self, i: usize) -> Option<&mut GameTreeNode<SIZE>> {
+    pub fn get_mut(&mut self, i: usize) -> Option<&mut GameTreeNode<G>> {
         self.states.get_mut(i)
     }
-    pub fn parent(&self, i: usize) -> Option<(usize, &GameTreeNode<SIZE>)> {
+    pub fn parent(&self, i: usize) -> Option<(usize, &GameTreeNode<G>)> {
         let ch = self.states.get(i)?;
         ch.parent(self).map(|x| (ch.parent, x))
     }
+    /// Number of moves from `ancestor` down to `i`, None if `ancestor` isn't on the way to the root
+    pub fn depth(&self, ancestor: usize, i: usize) -> Option<usize> {
+        iter::successors(Some(i), |&i| self.get(i).map(|node| node.parent))
+            .take(self.states.len())
+            .position(|i| i == ancestor)
+    }
+    /// Nodes from `ancestor` down to `i`, both included
+    pub fn path(&self, ancestor: usize, i: usize) -> Option<Vec<usize>> {
+        let mut path = iter::successors(Some(i), |&i| self.get(i).map(|node| node.parent))
+            .take(self.depth(ancestor, i)? + 1)
+            .collect_vec();
+        path.reverse();
+        Some(path)
+    }
     pub fn children_indices(&self, i: usize) -> Option<&SmallVec<[usize; 2]>> {
         Some(&self.states.get(i)?.children)
     }
-    pub fn children(&self, i: usize) -> Option<impl Iterator<Item = (usize, &GameTreeNode<SIZE>)>> {
+    pub fn children(&self, i: usize) -> Option<impl Iterator<Item = (usize, &GameTreeNode<G>)>> {
         let ch = self.states.get(i)?;
         Some(ch.children.iter().copied().zip(ch.children(self)))
     }
@@ -132,22 +156,37 @@ impl<const SIZE: u8> GameTree<SIZE> {
         boardi: usize,
         from: Position,
         to: Position,
-    ) -> Option<(bool, usize, Board<SIZE>)> {
+    ) -> Option<(bool, usize, Board<G>)> {
         let board = self.get(boardi)?.board.apply_move(from, to)?;
         let (new, idx) = self.push(boardi, board);
         Some((new, idx, board))
     }
 
-    pub fn explore(&mut self, from: usize) -> Vec<usize> {
+    /// Play `jumps` one after another from `boardi`, the node reached
+    pub fn play(&mut self, boardi: usize, jumps: &[Jump]) -> usize {
+        jumps.iter().fold(boardi, |at, &(from, _, to)| {
+            let (_, idx, _) = self.apply_move(at, from, to).expect("Valid jumps");
+            idx
+        })
+    }
+
+    pub fn explore(&mut self, from: usize, goal: &Goal<G>) -> Vec<usize> {
+        let root = self.get(from).unwrap().board;
+        let pagodas = Pagodas::for_goal(goal, &root);
         let mut qu = VecDeque::with_capacity(1024);
         let mut succ = Vec::with_capacity(256);
-        qu.push_back(from);
+        qu.push_back((from, pagodas.values(&root)));
 
-        while let Some(brd_idx) = qu.pop_back() {
+        while let Some((brd_idx, values)) = qu.pop_back() {
             let board = self.get(brd_idx).unwrap().board;
 
-            if board.count() == 1 {
+            if goal.reached(&board) {
                 succ.push(brd_idx);
+                continue;
+            }
+            // Provably unsolvable, don't expand
+            if !pagodas.finishable(&values, board.count()) {
+                continue;
             }
 
             for (from, over, to) in board.all_valid_moves() {
@@ -156,7 +195,9 @@ impl<const SIZE: u8> GameTree<SIZE> {
                     .apply_move(brd_idx, from, to)
                     .expect("Valid by construction");
                 if !existing {
-                    qu.push_back(idx);
+                    let mut values = values.clone();
+                    pagodas.apply(&mut values, from, over, to);
+                    qu.push_back((idx, values));
                 }
             }
         }
@@ -164,7 +205,11 @@ impl<const SIZE: u8> GameTree<SIZE> {
         succ
     }
 
-    pub fn check_solvable(&self, from: usize) -> Option<impl Iterator<Item = usize> + '_> {
-        Some(self.get(from)?.solvable(self))
+    pub fn check_solvable<'a>(
+        &'a self,
+        from: usize,
+        goal: &'a Goal<G>,
+    ) -> Option<impl Iterator<Item = usize> + 'a> {
+        Some(self.get(from)?.solvable(self, goal))
     }
 }
@@ -0,0 +1,380 @@
+use std::{f32::consts::PI, fmt::Debug, hash::Hash, marker::PhantomData, mem::size_of_val, ops::Range};
+
+use boolinator::Boolinator;
+
+use crate::{
+    board::{pos, Position},
+    rules::Capture,
+};
+
+/// Symmetry of a board as a map on positions
+pub type Symmetry = fn(Position) -> Position;
+
+/// Shape of a board: which cells exist, how they are indexed, which way jumps go and how the
+/// board maps onto itself.
+/// Every row has to be a contiguous run of cells, apart from `blocked` ones, and there can be
+/// at most 64 cells
+pub trait Geometry: Copy + Clone + Debug + Default + PartialEq + Eq + Hash + Send + Sync + 'static {
+    const ROWS: u8;
+    /// Cells of row `y` are at these `x`
+    fn row(y: u8) -> Range<u8>;
+    /// Unit steps of the lattice, a jump moves two steps in one of these directions
+    const DIRECTIONS: &'static [(i8, i8)];
+    /// Maps sending the board onto itself, starting with the identity
+    const SYMMETRIES: &'static [Symmetry];
+    /// Lattice coordinates for drawing, neighbouring cells are one unit apart
+    fn layout(p: Position) -> (f32, f32);
+    /// Jumps go over a whole line of stones and remove all of them
+    const LONG_JUMPS: bool = false;
+    /// `DIRECTIONS` or the cells aren't the lattice's own, so only some of `SYMMETRIES` keep them
+    const RESTRICTED: bool = false;
+    /// Number of stone colours, at most 4
+    const COLOURS: u8 = 1;
+    /// Which colours of stone a stone may jump
+    const CAPTURE: Capture = Capture::Any;
+
+    /// Cells inside a row that are drawn but can never hold a stone or be jumped over
+    fn blocked(_p: Position) -> bool {
+        false
+    }
+    fn valid_pos(p: Position) -> bool {
+        p.y < Self::ROWS && Self::row(p.y).contains(&p.x) && !Self::blocked(p)
+    }
+    /// Bit index of `p`, cells numbered in row order
+    fn get_idx(p: Position) -> Option<usize> {
+        Self::valid_pos(p).as_some_from(|| row_index::<Self>(p))
+    }
+    fn get_pos(i: usize) -> Option<Position> {
+        let mut i = i;
+        for y in 0..Self::ROWS {
+            let row = Self::row(y);
+            if i < row.len() {
+                return Some(pos(row.start + i as u8, y));
+            }
+            i -= row.len();
+        }
+        None
+    }
+}
+
+/// Index of `p` counting every cell of the rows in order, blocked or not
+pub fn row_index<G: Geometry>(p: Position) -> usize {
+    (0..p.y).map(|y| G::row(y).len()).sum::<usize>() + (p.x - G::row(p.y).start) as usize
+}
+
+fn identity(p: Position) -> Position {
+    p
+}
+
+/// Triangle with `SIZE` holes along each edge, rows get shorter going down
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Triangle<const SIZE: u8>;
+
+/// Six directions of the triangular lattice
+pub const HEX_DIRECTIONS: &[(i8, i8)] = &[(1, 0), (0, 1), (-1, 1), (-1, 0), (0, -1), (1, -1)];
+/// Four directions of the square lattice
+pub const ORTHOGONAL_DIRECTIONS: &[(i8, i8)] = &[(1, 0), (0, 1), (-1, 0), (0, -1)];
+
+/// Lattice coordinates of the triangular lattice, the y axis leans to the right
+pub fn hex_layout(p: Position) -> (f32, f32) {
+    (p.x as f32 + p.y as f32 / 2., p.y as f32 * f32::sin(PI / 3.))
+}
+pub fn square_layout(p: Position) -> (f32, f32) {
+    (p.x as f32, p.y as f32)
+}
+
+impl<const SIZE: u8> Triangle<SIZE> {
+    pub fn row_len(y: u8) -> u8 {
+        SIZE - y
+    }
+    // Round up to power two so that mul becomes shl and mod becomes bitand, uses more memory
+    const ROW_OFFSET: usize = 1 << (size_of_val(&SIZE) * 8 - SIZE.leading_zeros() as usize);
+
+    fn flip(p: Position) -> Position {
+        pos(SIZE - 1 - p.x - p.y, p.y)
+    }
+    fn rotate_right(p: Position) -> Position {
+        pos(p.y, SIZE - 1 - p.x - p.y)
+    }
+    fn rotate_left(p: Position) -> Position {
+        pos(SIZE - 1 - p.y - p.x, p.x)
+    }
+    fn rotate_right_flip(p: Position) -> Position {
+        Self::flip(Self::rotate_right(p))
+    }
+    fn rotate_left_flip(p: Position) -> Position {
+        Self::flip(Self::rotate_left(p))
+    }
+}
+
+impl<const SIZE: u8> Geometry for Triangle<SIZE> {
+    const ROWS: u8 = SIZE;
+    fn row(y: u8) -> Range<u8> {
+        0..Self::row_len(y)
+    }
+    const DIRECTIONS: &'static [(i8, i8)] = HEX_DIRECTIONS;
+    const SYMMETRIES: &'static [Symmetry] = &[
+        identity,
+        Self::flip,
+        Self::rotate_right,
+        Self::rotate_left,
+        Self::rotate_right_flip,
+        Self::rotate_left_flip,
+    ];
+    fn layout(p: Position) -> (f32, f32) {
+        hex_layout(p)
+    }
+
+    fn get_idx(p: Position) -> Option<usize> {
+        Self::valid_pos(p).as_some((p.x as usize) | ((p.y as usize) * Self::ROW_OFFSET))
+    }
+    fn get_pos(i: usize) -> Option<Position> {
+        let p = pos((i % Self::ROW_OFFSET) as u8, (i / Self::ROW_OFFSET) as u8);
+        (i < Self::ROW_OFFSET * SIZE as usize && Self::valid_pos(p)).as_some(p)
+    }
+}
+
+/// Boards fitting in the square with corners (0, 0) and (M, M)
+trait SquareBox {
+    const M: u8;
+}
+/// The eight symmetries of a `SquareBox`
+struct Square8<B>(PhantomData<B>);
+impl<B: SquareBox> Square8<B> {
+    fn flip_x(p: Position) -> Position {
+        pos(B::M - p.x, p.y)
+    }
+    fn flip_y(p: Position) -> Position {
+        pos(p.x, B::M - p.y)
+    }
+    fn rotate_half(p: Position) -> Position {
+        pos(B::M - p.x, B::M - p.y)
+    }
+    fn transpose(p: Position) -> Position {
+        pos(p.y, p.x)
+    }
+    fn rotate_right(p: Position) -> Position {
+        pos(B::M - p.y, p.x)
+    }
+    fn rotate_left(p: Position) -> Position {
+        pos(p.y, B::M - p.x)
+    }
+    fn anti_transpose(p: Position) -> Position {
+        pos(B::M - p.y, B::M - p.x)
+    }
+    const ALL: &'static [Symmetry] = &[
+        identity,
+        Self::flip_x,
+        Self::flip_y,
+        Self::rotate_half,
+        Self::transpose,
+        Self::rotate_right,
+        Self::rotate_left,
+        Self::anti_transpose,
+    ];
+}
+
+/// The 33 hole English cross
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct English;
+
+impl SquareBox for English {
+    const M: u8 = 6;
+}
+impl Geometry for English {
+    const ROWS: u8 = 7;
+    fn row(y: u8) -> Range<u8> {
+        if (2..5).contains(&y) {
+            0..7
+        } else {
+            2..5
+        }
+    }
+    const DIRECTIONS: &'static [(i8, i8)] = ORTHOGONAL_DIRECTIONS;
+    const SYMMETRIES: &'static [Symmetry] = Square8::<Self>::ALL;
+    fn layout(p: Position) -> (f32, f32) {
+        square_layout(p)
+    }
+}
+
+/// The 37 hole French (European) board, the cross with the inner corners filled in
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct French;
+
+impl SquareBox for French {
+    const M: u8 = 6;
+}
+impl Geometry for French {
+    const ROWS: u8 = 7;
+    fn row(y: u8) -> Range<u8> {
+        match y {
+            0 | 6 => 2..5,
+            1 | 5 => 1..6,
+            _ => 0..7,
+        }
+    }
+    const DIRECTIONS: &'static [(i8, i8)] = ORTHOGONAL_DIRECTIONS;
+    const SYMMETRIES: &'static [Symmetry] = Square8::<Self>::ALL;
+    fn layout(p: Position) -> (f32, f32) {
+        square_layout(p)
+    }
+}
+
+/// Cells within taxicab distance `R` of the centre, `Diamond<4>` is the 41 hole board
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Diamond<const R: u8>;
+
+impl<const R: u8> SquareBox for Diamond<R> {
+    const M: u8 = 2 * R;
+}
+impl<const R: u8> Geometry for Diamond<R> {
+    const ROWS: u8 = 2 * R + 1;
+    fn row(y: u8) -> Range<u8> {
+        let w = R - y.abs_diff(R);
+        R - w..R + w + 1
+    }
+    const DIRECTIONS: &'static [(i8, i8)] = ORTHOGONAL_DIRECTIONS;
+    const SYMMETRIES: &'static [Symmetry] = Square8::<Self>::ALL;
+    fn layout(p: Position) -> (f32, f32) {
+        square_layout(p)
+    }
+}
+
+/// `N` by `N` square
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Square<const N: u8>;
+
+impl<const N: u8> SquareBox for Square<N> {
+    const M: u8 = N - 1;
+}
+impl<const N: u8> Geometry for Square<N> {
+    const ROWS: u8 = N;
+    fn row(_: u8) -> Range<u8> {
+        0..N
+    }
+    const DIRECTIONS: &'static [(i8, i8)] = ORTHOGONAL_DIRECTIONS;
+    const SYMMETRIES: &'static [Symmetry] = Square8::<Self>::ALL;
+    fn layout(p: Position) -> (f32, f32) {
+        square_layout(p)
+    }
+}
+
+/// Boards on the triangular lattice that are symmetric about the cell (C, C)
+trait HexCentre {
+    const C: u8;
+}
+/// Rotate `K` times by 60 degrees about the centre, after mirroring if `F`
+fn hex_symmetry<B: HexCentre, const K: u8, const F: bool>(p: Position) -> Position {
+    let c = B::C as i16;
+    let (mut q, mut r) = (p.x as i16 - c, p.y as i16 - c);
+    if F {
+        std::mem::swap(&mut q, &mut r);
+    }
+    for _ in 0..K {
+        let (nq, nr) = (-r, q + r);
+        q = nq;
+        r = nr;
+    }
+    pos((q + c) as u8, (r + c) as u8)
+}
+/// The twelve symmetries of the hexagon
+struct Hex12<B>(PhantomData<B>);
+impl<B: HexCentre> Hex12<B> {
+    const ALL: &'static [Symmetry] = &[
+        hex_symmetry::<B, 0, false>,
+        hex_symmetry::<B, 1, false>,
+        hex_symmetry::<B, 2, false>,
+        hex_symmetry::<B, 3, false>,
+        hex_symmetry::<B, 4, false>,
+        hex_symmetry::<B, 5, false>,
+        hex_symmetry::<B, 0, true>,
+        hex_symmetry::<B, 1, true>,
+        hex_symmetry::<B, 2, true>,
+        hex_symmetry::<B, 3, true>,
+        hex_symmetry::<B, 4, true>,
+        hex_symmetry::<B, 5, true>,
+    ];
+}
+
+/// Hexagon of cells within `R` steps of the centre, `Hexagon<3>` has 37 holes and `Hexagon<4>` 61
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Hexagon<const R: u8>;
+
+impl<const R: u8> HexCentre for Hexagon<R> {
+    const C: u8 = R;
+}
+impl<const R: u8> Geometry for Hexagon<R> {
+    const ROWS: u8 = 2 * R + 1;
+    fn row(y: u8) -> Range<u8> {
+        R.saturating_sub(y)..(2 * R).min(3 * R - y) + 1
+    }
+    const DIRECTIONS: &'static [(i8, i8)] = HEX_DIRECTIONS;
+    const SYMMETRIES: &'static [Symmetry] = Hex12::<Self>::ALL;
+    fn layout(p: Position) -> (f32, f32) {
+        let (x, y) = hex_layout(p);
+        (x - R as f32 / 2., y)
+    }
+}
+
+/// Six pointed star, two triangles of side `3 * T + 1` overlapping on a hexagon of radius `T`.
+/// `Star<2>` is the 37 hole star
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Star<const T: u8>;
+
+impl<const T: u8> HexCentre for Star<T> {
+    const C: u8 = 2 * T;
+}
+impl<const T: u8> Geometry for Star<T> {
+    const ROWS: u8 = 4 * T + 1;
+    fn row(y: u8) -> Range<u8> {
+        let (t, r) = (T as i16, y as i16 - 2 * T as i16);
+        let (start, end) = if r < -t {
+            (-t - r, t)
+        } else if r > t {
+            (-t, t - r)
+        } else {
+            ((-t - r).min(-t), t.max(t - r))
+        };
+        (start + 2 * t) as u8..(end + 2 * t + 1) as u8
+    }
+    const DIRECTIONS: &'static [(i8, i8)] = HEX_DIRECTIONS;
+    const SYMMETRIES: &'static [Symmetry] = Hex12::<Self>::ALL;
+    fn layout(p: Position) -> (f32, f32) {
+        let (x, y) = hex_layout(p);
+        (x - 3. * T as f32 / 2., y)
+    }
+}
+
+/// `G` with the cells set in `MASK` blocked, bits in `row_index` order.
+/// `Masked<Triangle<5>, 0x4011>` is the triangle without its corners
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Masked<G, const MASK: u64>(PhantomData<G>);
+
+impl<G: Geometry, const MASK: u64> Geometry for Masked<G, MASK> {
+    const ROWS: u8 = G::ROWS;
+    fn row(y: u8) -> Range<u8> {
+        G::row(y)
+    }
+    const DIRECTIONS: &'static [(i8, i8)] = G::DIRECTIONS;
+    const SYMMETRIES: &'static [Symmetry] = G::SYMMETRIES;
+    fn layout(p: Position) -> (f32, f32) {
+        G::layout(p)
+    }
+    const LONG_JUMPS: bool = G::LONG_JUMPS;
+    const RESTRICTED: bool = MASK != 0 || G::RESTRICTED;
+    const COLOURS: u8 = G::COLOURS;
+    const CAPTURE: Capture = G::CAPTURE;
+
+    fn blocked(p: Position) -> bool {
+        G::blocked(p) || (G::valid_pos(p) && MASK >> row_index::<G>(p) & 1 == 1)
+    }
+    fn valid_pos(p: Position) -> bool {
+        G::valid_pos(p) && !Self::blocked(p)
+    }
+    fn get_idx(p: Position) -> Option<usize> {
+        G::get_idx(p).filter(|_| !Self::blocked(p))
+    }
+    fn get_pos(i: usize) -> Option<Position> {
+        G::get_pos(i).filter(|&p| !Self::blocked(p))
+    }
+}
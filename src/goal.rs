@@ -0,0 +1,86 @@
+use itertools::Itertools;
+
+use crate::{
+    board::{Board, Position},
+    geometry::Geometry,
+};
+
+/// What counts as a solved board
+#[derive(Debug, Copy, Clone, Default)]
+pub enum Goal<G: Geometry> {
+    /// A single stone anywhere
+    #[default]
+    AnyOne,
+    /// A single stone on this hole, `Hole(start)` is the complement problem
+    Hole(Position),
+    /// No more than this many stones
+    AtMost(usize),
+    /// Exactly this board
+    Pattern(Board<G>),
+    /// Any board the predicate accepts
+    Custom(fn(&Board<G>) -> bool),
+    /// Fool's solitaire: no jump left while at least this many stones remain
+    Stuck(usize),
+    /// A single stone of this colour
+    Colour(u8),
+}
+
+impl<G: Geometry> Goal<G> {
+    /// Finish where the only hole of `start` is, None if `start` doesn't have exactly one hole
+    pub fn complement(start: &Board<G>) -> Option<Self> {
+        start
+            .iter_holes()
+            .exactly_one()
+            .ok()
+            .map(Goal::Hole)
+    }
+
+    pub fn reached(&self, board: &Board<G>) -> bool {
+        match *self {
+            Goal::AnyOne => board.count() == 1,
+            Goal::Hole(p) => board.count() == 1 && board.at(p).unwrap_or(false),
+            Goal::AtMost(n) => board.count() <= n,
+            Goal::Pattern(target) => *board == target,
+            Goal::Custom(f) => f(board),
+            Goal::Stuck(n) => board.count() >= n && board.all_valid_moves().next().is_none(),
+            Goal::Colour(c) => board.count() == 1 && board.iter_colours().all(|(_, colour)| colour == c),
+        }
+    }
+    /// Every board that reaches the goal, None if there is no useful finite list
+    pub fn targets(&self) -> Option<Vec<Board<G>>> {
+        match *self {
+            Goal::AnyOne => Some(Board::<G>::iter_all().map(Board::single).collect()),
+            Goal::Hole(p) => Some(vec![Board::single(p)]),
+            Goal::Pattern(target) => Some(vec![target]),
+            Goal::Colour(c) => Some(
+                Board::<G>::iter_all()
+                    .filter_map(|p| Board::single(p).with_colour(p, c))
+                    .collect(),
+            ),
+            Goal::AtMost(_) | Goal::Custom(_) | Goal::Stuck(_) => None,
+        }
+    }
+    /// Cheap check that the goal isn't already out of reach, for every node of a search
+    pub fn alive(&self, board: &Board<G>) -> bool {
+        match *self {
+            Goal::Stuck(n) => board.count() >= n,
+            // Stones keep their colour, so the last one has to be there already
+            Goal::Colour(c) => board.iter_colours().any(|(_, colour)| colour == c),
+            _ => true,
+        }
+    }
+    /// false if no invariant allows `board` to reach the goal, doesn't search
+    pub fn may_reach(&self, board: &Board<G>) -> bool {
+        self.targets()
+            .map(|ts| ts.iter().any(|t| board.may_reach(t)))
+            .unwrap_or(true)
+    }
+    /// Whether every symmetry of a board that reaches the goal also reaches it,
+    /// if so boards can be canonicalized while searching
+    pub fn symmetric(&self) -> bool {
+        match self {
+            Goal::AnyOne | Goal::AtMost(_) | Goal::Stuck(_) | Goal::Colour(_) => true,
+            Goal::Hole(_) | Goal::Pattern(_) | Goal::Custom(_) => false,
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::cmp::Reverse;
+
+use fxhash::FxHashMap;
+use itertools::Itertools;
+
+use crate::{
+    board::Board,
+    geometry::Geometry,
+    goal::Goal,
+    packages::{self, Placement},
+    solver::Solver,
+    strategy::{Jump, Perfect},
+};
+
+/// What perfect play can still get out of a jump
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Hint {
+    pub jump: Jump,
+    /// The goal can still be reached after this jump
+    pub solvable: bool,
+    /// Sequences of jumps reaching the goal after this one
+    pub solutions: u64,
+    /// Fewest stones the board can still be played down to
+    pub fewest: usize,
+}
+
+/// Exact evaluation of every jump, remembers boards between calls
+#[derive(Debug, Clone)]
+pub struct Hints<G: Geometry> {
+    solver: Solver<G>,
+    perfect: Perfect<G>,
+    /// Canonical if the goal is symmetric
+    solutions: FxHashMap<Board<G>, u64>,
+}
+
+impl<G: Geometry> Hints<G> {
+    pub fn new(goal: Goal<G>) -> Self {
+        Self {
+            solver: Solver::new(goal),
+            perfect: Perfect::default(),
+            solutions: FxHashMap::default(),
+        }
+    }
+    pub fn goal(&self) -> &Goal<G> {
+        self.solver.goal()
+    }
+
+    /// Number of sequences of jumps from `board` that reach the goal, stopping when it is reached
+    pub fn solutions(&mut self, board: &Board<G>) -> u64 {
+        if self.goal().reached(board) {
+            return 1;
+        }
+        if !self.solver.solvable(board) {
+            return 0;
+        }
+        let key = if self.goal().symmetric() {
+            board.canonicalize()
+        } else {
+            *board
+        };
+        if let Some(&n) = self.solutions.get(&key) {
+            return n;
+        }
+        let n = board
+            .all_valid_moves()
+            .map(|(from, _, to)| board.apply_move(from, to).expect("Valid by construction"))
+            .collect_vec()
+            .iter()
+            .map(|next| self.solutions(next))
+            .fold(0, u64::saturating_add);
+        self.solutions.insert(key, n);
+        n
+    }
+
+    pub fn hints(&mut self, board: &Board<G>) -> Vec<Hint> {
+        board
+            .all_valid_moves()
+            .collect_vec()
+            .into_iter()
+            .map(|jump| {
+                let (from, _, to) = jump;
+                let next = board.apply_move(from, to).expect("Valid by construction");
+                Hint {
+                    jump,
+                    solvable: self.solver.solvable(&next),
+                    solutions: self.solutions(&next),
+                    fewest: self.perfect.fewest(&next),
+                }
+            })
+            .collect()
+    }
+    /// Packages that keep the goal in reach
+    pub fn packages(&mut self, board: &Board<G>) -> Vec<Placement> {
+        packages::hints(&mut self.solver, board)
+    }
+    /// Keeps the goal in reach with the most solutions, otherwise leaves the fewest stones
+    pub fn best(&mut self, board: &Board<G>) -> Option<Hint> {
+        self.hints(board)
+            .into_iter()
+            .max_by_key(|h| (h.solvable, h.solutions, Reverse(h.fewest)))
+    }
+}
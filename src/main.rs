@@ -1,28 +1,62 @@
 #![feature(default_free_fn)]
 #![feature(const_size_of_val)]
 
-#[macro_use]
-mod lazy_fixed_iter;
+mod ai;
+mod analysis;
 mod board;
+mod cli;
+mod commutation;
+mod daily;
 mod gamestate;
+mod geometry;
+mod goal;
+mod hint;
+mod moves;
+mod packages;
+mod pagoda;
+mod puzzle;
+mod rank;
+mod reach;
+mod rules;
+mod score;
+mod solutions;
+mod solver;
+mod starts;
+mod strategy;
 mod u32set;
+mod versus;
 
-use std::f32::consts::PI;
+use std::{
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
 
-use board::Position;
+use ai::{Decision, Opponent, Strength};
+use analysis::Analysis;
+use board::{Board, Position};
 use boolinator::Boolinator;
+use daily::{Attempt, Challenge, Date};
+use gamestate::GameTree;
+use geometry::{Diamond, English, French, Geometry, Hexagon, Masked, Square, Star, Triangle};
+use goal::Goal;
+use hint::{Hint, Hints};
+use moves::MoveSolver;
+use rules::{Colours, Diagonal, Long, Orthogonal, OtherColour, SameColour, Variant};
+use score::Tally;
+use solutions::{solution_text, Dedup, Solutions};
+use strategy::Jump;
+use versus::{Outcome, Play, Versus};
 
 use itertools::Itertools;
 use speedy2d::{
     color::Color,
     dimen::Vector2,
+    font::{Font, TextLayout, TextOptions},
     window::{self, KeyScancode, MouseButton, VirtualKeyCode, WindowHandler, WindowHelper},
     Graphics2D, Window,
 };
 
 const BOARD_SIZE: u8 = 5;
-type GameTree = gamestate::GameTree<BOARD_SIZE>;
-type Board = board::Board<BOARD_SIZE>;
 
 const MOUSE_LEFT_KEY: char = 'a';
 const MOUSE_MIDDLE_KEY: char = 's';
@@ -30,6 +64,26 @@ const MOUSE_RIGHT_KEY: char = 'd';
 const ESC_KEY: char = 'q';
 
 const STONE_RADIUS: f32 = 30.0;
+/// Room beside the board for the analysis report
+const REPORT_WIDTH: f32 = 480.0;
+const REPORT_TEXT_SIZE: f32 = 14.0;
+/// Noto Sans, licensed under the SIL Open Font License in assets/OFL.txt
+const FONT: &[u8] = include_bytes!("../assets/NotoSans-Regular.ttf");
+/// Where the post-game analysis is saved
+const ANALYSIS_FILE: &str = "analysis.txt";
+/// Position to jump to, in the board notation
+const PASTE_FILE: &str = "paste.txt";
+/// Game to import, a board after each move
+const RECORD_FILE: &str = "record.txt";
+/// Random games per jump for the estimates overlay
+const ROLLOUTS: usize = 2000;
+/// Time the two player solve gets each frame, the next frame carries on where it stopped
+const VERSUS_BUDGET: Duration = Duration::from_millis(50);
+
+/// Computer's search on another thread, handing the opponent back with its decision
+type Search<G> = JoinHandle<(Opponent<G>, Option<Decision>)>;
+/// Share of random games reaching the goal after each jump
+type Estimates = Vec<(Jump, f64)>;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum State {
@@ -40,17 +94,52 @@ enum State {
 const INITIAL: State = State::SelectStart;
 
 #[derive(Debug)]
-struct MyWindowHandler {
+struct MyWindowHandler<G: Geometry> {
     mouse: Vector2<f32>,
     keys: String,
     mods: Option<window::ModifiersState>,
     state: State,
 
-    tree: GameTree,
+    tree: GameTree<G>,
     current: usize,
+    goal: Goal<G>,
+    /// Where the game left `SelectStart`, turns are counted from here
+    start: usize,
+    /// Two players taking turns instead of solitaire
+    versus: Option<Versus<G>>,
+    /// Computer playing second in the two player game
+    opponent: Option<Opponent<G>>,
+    /// Computer searching on another thread for its jump from the board at this index
+    thinking: Option<(usize, Search<G>)>,
+    /// Show how often random play reaches the goal after each jump
+    estimating: bool,
+    /// Estimates for the board they were made for
+    estimates: Option<(Board<G>, Estimates)>,
+    /// Colour the jumps by what perfect play can still get out of them
+    hinting: bool,
+    /// Hint engine for the current goal, with the hints for the board they were made for
+    hints: Option<(Hints<G>, Board<G>, Vec<Hint>)>,
+    /// Last post-game analysis, its blunder is drawn when its board is shown
+    analysis: Option<Analysis<G>>,
+    /// For the analysis report
+    font: Font,
+    /// Today's challenge while it is being played
+    daily: Option<Attempt<G>>,
+    /// Hints, undos and time of the game since `start`
+    tally: Tally,
+    /// Board and rules picked on the command line, games are scored against others on it
+    name: String,
+    /// Count moves in the title, a chain of jumps by one stone being one move.
+    /// Holds the count last shown
+    counting: Option<usize>,
+    /// Lines of play that differ by more than the order of commuting jumps, from the board at
+    /// this index, with how many were shown
+    lines: Option<(usize, Solutions<G>, usize)>,
+    /// Board marked to go to
+    target: Option<Board<G>>,
 }
 
-impl MyWindowHandler {
+impl<G: Geometry> MyWindowHandler<G> {
     fn new() -> Self {
         Self {
             mouse: Vector2::<f32>::ZERO,
@@ -59,15 +148,35 @@ impl MyWindowHandler {
             // tree: GameTree::start(Board::start(Position::new(0, 0))),
             tree: GameTree::start(Board::full()),
             current: 0,
+            goal: Goal::AnyOne,
             state: INITIAL,
+            start: 0,
+            versus: None,
+            opponent: None,
+            thinking: None,
+            estimating: false,
+            estimates: None,
+            hinting: false,
+            hints: None,
+            analysis: None,
+            font: Font::new(FONT).expect("Bundled font"),
+            daily: None,
+            tally: Tally::default(),
+            name: cli::board_name(),
+            counting: None,
+            lines: None,
+            target: None,
         }
     }
 }
 
-impl WindowHandler for MyWindowHandler {
+impl<G: Geometry> WindowHandler for MyWindowHandler<G> {
     fn on_start(&mut self, helper: &mut WindowHelper<()>, info: window::WindowStartupInfo) {}
 
-    fn on_user_event(&mut self, helper: &mut WindowHelper<()>, user_event: ()) {}
+    fn on_user_event(&mut self, helper: &mut WindowHelper<()>, user_event: ()) {
+        // The computer found its jump
+        helper.request_redraw();
+    }
 
     fn on_resize(&mut self, helper: &mut WindowHelper<()>, size_pixels: Vector2<u32>) {}
 
@@ -85,7 +194,7 @@ impl WindowHandler for MyWindowHandler {
         let board = node.board;
 
         let hover = board.iter().find(|&(pos, _)| {
-            (self.mouse - stone_pos(pos)).magnitude_squared() < STONE_RADIUS * STONE_RADIUS
+            (self.mouse - stone_pos::<G>(pos)).magnitude_squared() < STONE_RADIUS * STONE_RADIUS
         });
         let hover_stone = hover.and_then(|(hover, at)| at.as_some(hover));
 
@@ -95,9 +204,22 @@ impl WindowHandler for MyWindowHandler {
         //     .map(|it| it.collect_vec())
         //     .unwrap_or(Vec::new());
         // let solvable = solutions.len() > 0;
-        let num_solutions = node.num_solutions(&self.tree);
+        let num_solutions = node.num_solutions(&self.tree, &self.goal);
         let solvable = num_solutions > 0;
-        graphics.clear_screen(if solvable { Color::WHITE } else { Color::GRAY });
+        // Whose turn it is, 0 or 1
+        let player = self.tree.depth(self.start, self.current).unwrap_or(0) % 2;
+        let playing = self.state != State::SelectStart;
+        let outcomes = self
+            .versus
+            .as_mut()
+            .filter(|_| playing)
+            .map(|versus| versus.outcomes(&board, Instant::now() + VERSUS_BUDGET));
+        graphics.clear_screen(match (&outcomes, player) {
+            (Some(_), 0) => Color::from_rgb(0.85, 0.9, 1.0),
+            (Some(_), _) => Color::from_rgb(1.0, 0.88, 0.88),
+            (None, _) if solvable => Color::WHITE,
+            (None, _) => Color::GRAY,
+        });
 
         {
             // Process keypresses
@@ -106,13 +228,23 @@ impl WindowHandler for MyWindowHandler {
                     MOUSE_LEFT_KEY | MOUSE_RIGHT_KEY => {
                         match self.state {
                             State::SelectStart => {
-                                if let Some(stone) = hover_stone {
+                                if key == MOUSE_RIGHT_KEY {
+                                    // Toggle holes to build up any start
+                                    if let Some((pos, stone)) = hover {
+                                        let new = board.with_stone(pos, !stone).expect("Hovered");
+                                        self.current = self.tree.push(self.current, new).1;
+                                    }
+                                } else if let Some(stone) = hover_stone {
                                     let new = board.filter(|&old| old != stone);
                                     // let new = new.canonicalize();
                                     self.current = self.tree.push(self.current, new).1;
+                                    self.start = self.current;
                                     self.state = State::Idle;
+                                    self.tally = Tally::default();
                                 }
                             }
+                            // The computer is choosing its jump
+                            State::Idle | State::PickUpStone(_) if self.thinking.is_some() => {}
                             State::Idle => {
                                 if let Some(stone) = hover_stone {
                                     self.state = State::PickUpStone(stone)
@@ -133,7 +265,8 @@ impl WindowHandler for MyWindowHandler {
 
                                             // let new = new.canonicalize();
                                             self.current = self.tree.push(self.current, new).1;
-                                            if key == MOUSE_LEFT_KEY {
+                                            // One jump a turn in the two player game
+                                            if key == MOUSE_LEFT_KEY || self.versus.is_some() {
                                                 self.state = State::Idle;
                                             } else {
                                                 self.state = State::PickUpStone(stone)
@@ -144,7 +277,12 @@ impl WindowHandler for MyWindowHandler {
                             }
                         }
                     }
-                    MOUSE_MIDDLE_KEY => {}
+                    // Start from the board as it is
+                    MOUSE_MIDDLE_KEY if self.state == State::SelectStart && self.current != 0 => {
+                        self.start = self.current;
+                        self.state = State::Idle;
+                        self.tally = Tally::default();
+                    }
                     ESC_KEY => match self.state {
                         State::Idle => {}
                         State::PickUpStone(_) => self.state = State::Idle,
@@ -165,7 +303,9 @@ impl WindowHandler for MyWindowHandler {
                                     assert_eq!(refresh, (true, self.current)); // Push this state to the "top of the redo stack"
                                 }
                                 self.current = idx;
-                                if self.current == 0 {
+                                self.tally.undos += 1;
+                                // Back at the start, or before it while building it up
+                                if let None | Some(0) = self.tree.depth(self.start, self.current) {
                                     self.state = State::SelectStart;
                                 }
                             } // else already at origin probably
@@ -212,20 +352,451 @@ impl WindowHandler for MyWindowHandler {
                         }
                         State::SelectStart => {}
                         State::Idle => {
-                            let succs = self.tree.explore(self.current);
+                            let succs = self.tree.explore(self.current, &self.goal);
                             if let Some(&fin) = succs.first() {
                                 self.current = fin;
+                                self.tally.hints += 1;
                             }
                         }
                     },
                     'c' => println!("{}", num_solutions),
+                    // Finish on the hovered hole, or where the game started without one
+                    'g' => {
+                        if let Some((hole, _)) = hover {
+                            self.goal = Goal::Hole(hole);
+                            self.hints = None;
+                            if let Err(why) = board.reachability(&Board::single(hole)) {
+                                println!("{:?} is impossible: {}", self.goal, why);
+                            }
+                        } else if let Some(goal) = self
+                            .tree
+                            .get(self.start)
+                            .and_then(|node| Goal::complement(&node.board))
+                        {
+                            self.goal = goal;
+                            self.hints = None;
+                            if !goal.may_reach(&board) {
+                                println!("{:?} is impossible", goal);
+                            }
+                        }
+                    }
+                    // Finish anywhere
+                    'G' => {
+                        self.goal = Goal::AnyOne;
+                        self.hints = None;
+                    }
+                    // Fool's solitaire, get stuck with as many stones as possible
+                    'f' => {
+                        if let Some((n, _)) = solver::fools_solitaire(&board) {
+                            println!("Fool's solitaire: can get stuck with {} stones", n);
+                            self.goal = Goal::Stuck(n);
+                            self.hints = None;
+                        }
+                    }
+                    // Repaint the hovered stone
+                    'k' => {
+                        if let (State::SelectStart, Some(stone)) = (self.state, hover_stone) {
+                            let colour = board.colour_at(stone).expect("Hovered");
+                            let new = board
+                                .with_colour(stone, (colour + 1) % G::COLOURS)
+                                .expect("Hovered");
+                            self.current = self.tree.push(self.current, new).1;
+                        }
+                    }
+                    // Finish with a single stone the colour of the hovered one
+                    'K' => {
+                        if let Some(stone) = hover_stone {
+                            self.goal = Goal::Colour(board.colour_at(stone).expect("Hovered"));
+                            self.hints = None;
+                        }
+                    }
+                    // Cycle solitaire, two players and two players misère
+                    'v' => {
+                        self.versus = match self.versus.as_ref().map(Versus::play) {
+                            None => Some(Versus::new(Play::Normal)),
+                            Some(Play::Normal) => Some(Versus::new(Play::Misere)),
+                            Some(Play::Misere) => None,
+                        };
+                        match &self.versus {
+                            Some(versus) => {
+                                println!("Two players, {}, A for a computer opponent", versus.play())
+                            }
+                            None => println!("Solitaire"),
+                        }
+                        self.thinking = None;
+                        self.opponent = match (&self.versus, &self.opponent) {
+                            (Some(versus), Some(opponent)) => {
+                                Some(Opponent::new(versus.play(), opponent.strength()))
+                            }
+                            _ => None,
+                        };
+                    }
+                    // Cycle the computer's strength, or play both sides. Not 'a', that is the left
+                    // mouse button
+                    'A' => {
+                        let play = self.versus.as_ref().map_or(Play::Normal, Versus::play);
+                        self.versus.get_or_insert_with(|| Versus::new(play));
+                        let next = match self.opponent.as_ref().map(Opponent::strength) {
+                            None => Some(Strength::Easy),
+                            Some(Strength::Easy) => Some(Strength::Medium),
+                            Some(Strength::Medium) => Some(Strength::Hard),
+                            Some(Strength::Hard) => Some(Strength::Perfect),
+                            Some(Strength::Perfect) => None,
+                        };
+                        self.thinking = None;
+                        self.opponent = next.map(|strength| Opponent::new(play, strength));
+                        match next {
+                            Some(strength) => println!("Computer plays second, {:?}", strength),
+                            None => println!("Hot-seat, no computer"),
+                        }
+                    }
+                    // Who wins from each start hole
+                    'w' => {
+                        let play = self.versus.as_ref().map_or(Play::Normal, Versus::play);
+                        for (hole, outcome) in versus::report::<G>(play) {
+                            println!("{:?} first player {}s ({})", hole, outcome, play);
+                        }
+                    }
+                    // Exact hints for each jump
+                    'h' => {
+                        self.hinting = !self.hinting;
+                        if self.hinting {
+                            self.tally.hints += 1;
+                            let goal = self.goal;
+                            let (hints, hinted, cached) = self
+                                .hints
+                                .get_or_insert_with(|| (Hints::new(goal), board, Vec::new()));
+                            // Kept for drawing them
+                            *cached = hints.hints(&board);
+                            *hinted = board;
+                            for hint in cached.iter() {
+                                let (from, _, to) = hint.jump;
+                                println!(
+                                    "{:?} to {:?}: {} solutions, down to {} stones",
+                                    from, to, hint.solutions, hint.fewest
+                                );
+                            }
+                            for placement in hints.packages(&board) {
+                                println!("{}", placement);
+                            }
+                        }
+                    }
+                    // Play the best jump
+                    'b' if self.state == State::Idle => {
+                        let goal = self.goal;
+                        let (hints, _, _) = self
+                            .hints
+                            .get_or_insert_with(|| (Hints::new(goal), board, Vec::new()));
+                        if let Some(hint) = hints.best(&board) {
+                            let (from, _, to) = hint.jump;
+                            let new = board.apply_move(from, to).expect("Valid by construction");
+                            self.current = self.tree.push(self.current, new).1;
+                            self.tally.hints += 1;
+                        }
+                    }
+                    // Analyse the game so far against perfect play
+                    'z' => {
+                        let (tree, goal) = (&self.tree, self.goal);
+                        let boards = tree.path(self.start, self.current).map(|path| {
+                            path.iter()
+                                .map(|&i| tree.get(i).expect("On the path").board)
+                                .collect_vec()
+                        });
+                        match boards.and_then(|boards| analysis::analyse(&boards, goal)) {
+                            Some(analysis) => {
+                                print!("{}", analysis);
+                                helper.set_title(&analysis.summary());
+                                match std::fs::write(ANALYSIS_FILE, analysis.to_string()) {
+                                    Ok(()) => println!("Saved to {}", ANALYSIS_FILE),
+                                    Err(e) => println!("Couldn't save {}: {}", ANALYSIS_FILE, e),
+                                }
+                                self.analysis = Some(analysis);
+                                helper.set_size_pixels(window_size::<G>(true));
+                            }
+                            None => println!("No game to analyse"),
+                        }
+                    }
+                    // Hide the analysis report
+                    'Z' => {
+                        if self.analysis.take().is_some() {
+                            helper.set_size_pixels(window_size::<G>(false));
+                        }
+                    }
+                    // Count moves instead of jumps
+                    'n' => {
+                        self.counting = match self.counting {
+                            None => Some(usize::MAX),
+                            Some(_) => None,
+                        };
+                    }
+                    // Fewest moves from here
+                    'N' => match MoveSolver::new(self.goal).solve(&board) {
+                        Some(chains) => {
+                            println!("{} moves", chains.len());
+                            for chain in chains {
+                                let (from, _, _) = chain[0];
+                                let landings = chain.iter().map(|&(_, _, to)| to).collect_vec();
+                                println!("{:?} to {:?}", from, landings);
+                            }
+                        }
+                        None => println!("{:?} can't be reached", self.goal),
+                    },
+                    // Solution made of packages where they fit
+                    'P' => {
+                        self.tally.hints += 1;
+                        cli::macro_solution(board, self.goal);
+                    }
+                    // Play out the next distinct line from where browsing started
+                    'l' if self.state == State::Idle => {
+                        let tree = &self.tree;
+                        let current = self.current;
+                        let goal = self.goal;
+                        let (origin, mut lines, shown) = self
+                            .lines
+                            .take()
+                            .filter(|&(origin, _, _)| tree.path(origin, current).is_some())
+                            .unwrap_or_else(|| {
+                                let dedup = Dedup {
+                                    symmetry: false,
+                                    commuting: true,
+                                };
+                                (current, Solutions::new(board, goal, dedup), 0)
+                            });
+                        match lines.next() {
+                            Some(line) => {
+                                println!("Line {}: {}", shown + 1, commutation::foata_text(&line));
+                                self.current = self.tree.play(origin, &line);
+                                self.lines = Some((origin, lines, shown + 1));
+                            }
+                            None => println!("{} distinct lines", shown),
+                        }
+                    }
+                    // Mark this board to come back to
+                    'x' => {
+                        println!("Marked as the target");
+                        print!("{}", board);
+                        self.target = Some(board);
+                    }
+                    // Finish on the marked board
+                    'T' => {
+                        if let Some(target) = self.target {
+                            self.goal = Goal::Pattern(target);
+                            self.hints = None;
+                            if let Err(why) = board.reachability(&target) {
+                                println!("The marked board is impossible: {}", why);
+                            }
+                        }
+                    }
+                    // Play from here to the marked board
+                    'X' => {
+                        if let (State::Idle, Some(target)) = (self.state, self.target) {
+                            match reach::diff(&board, &target) {
+                                Ok(jumps) => {
+                                    println!("{} jumps to the target", jumps.len());
+                                    self.current = self.tree.play(self.current, &jumps);
+                                }
+                                Err(why) => println!("Can't get to the target: {}", why),
+                            }
+                        }
+                    }
+                    // Play from here to the board in the paste file
+                    'j' => {
+                        if let (State::Idle, Some(pasted)) =
+                            (self.state, cli::read_board::<G>(PASTE_FILE))
+                        {
+                            match reach::diff(&board, &pasted) {
+                                Ok(jumps) => {
+                                    println!("Played {}", solution_text(&jumps));
+                                    self.current = self.tree.play(self.current, &jumps);
+                                }
+                                Err(why) => println!("Can't get to the pasted board: {}", why),
+                            }
+                        }
+                    }
+                    // Import a game recorded as its boards
+                    'J' => {
+                        let boards = std::fs::read_to_string(RECORD_FILE)
+                            .map_err(|e| e.to_string())
+                            .and_then(|text| {
+                                reach::parse_record::<G>(&text).map_err(|e| e.to_string())
+                            });
+                        match boards {
+                            Ok(boards) if boards.is_empty() => {
+                                println!("{} has no boards", RECORD_FILE)
+                            }
+                            Ok(boards) => match reach::replay(&boards) {
+                                Ok(jumps) => {
+                                    println!("Imported {}", solution_text(&jumps));
+                                    self.hints = None;
+                                    self.versus = None;
+                                    self.opponent = None;
+                                    self.thinking = None;
+                                    self.start = self.tree.push(0, boards[0]).1;
+                                    self.current = self.tree.play(self.start, &jumps);
+                                    self.state = State::Idle;
+                                    self.tally = Tally::default();
+                                    self.daily = None;
+                                }
+                                Err((i, why)) => println!(
+                                    "Board {} doesn't follow from the one before: {}",
+                                    i + 1,
+                                    why
+                                ),
+                            },
+                            Err(e) => println!("Couldn't import {}: {}", RECORD_FILE, e),
+                        }
+                    }
+                    // Play today's challenge
+                    'y' => {
+                        let challenge = Challenge::<G>::new(Date::today());
+                        println!("Daily challenge {}", challenge);
+                        helper.set_title(&challenge.to_string());
+                        self.goal = challenge.goal;
+                        self.hints = None;
+                        self.versus = None;
+                        self.opponent = None;
+                        self.thinking = None;
+                        self.current = self.tree.push(0, challenge.start).1;
+                        self.start = self.current;
+                        self.state = State::Idle;
+                        self.tally = Tally::default();
+                        self.daily = Some(Attempt::new(challenge));
+                    }
+                    // Today's results
+                    'Y' => cli::daily::<G>(Date::today()),
+                    // Monte Carlo estimates of each jump
+                    'e' => {
+                        self.estimating = !self.estimating;
+                        if self.estimating {
+                            self.tally.hints += 1;
+                            let estimates = strategy::estimates(&board, &self.goal, ROLLOUTS, 0);
+                            for &((from, _, to), p) in &estimates {
+                                println!("{:?} to {:?}: {:.2}%", from, to, p * 100.);
+                            }
+                            // Kept for drawing them
+                            self.estimates = Some((board, estimates));
+                        }
+                    }
+                    // Where can this board finish
+                    'i' => println!("{}", starts::report(Some(board))[0]),
+                    // Explain whether the hovered hole can hold the last stone
+                    'p' => {
+                        if let Some((hole, _)) = hover {
+                            match board.reachability(&Board::single(hole)) {
+                                Ok(()) => println!("{:?} could hold the last stone", hole),
+                                Err(why) => println!("{:?} can't hold the last stone: {}", hole, why),
+                            }
+                        }
+                    }
                     _ => {}
                 }
             }
         }
 
-        for pos in Board::iter_all() {
-            let stone = stone_pos(pos);
+        // Computer's turn, searched on another thread so the window keeps drawing
+        if let Some((at, handle)) = self.thinking.take() {
+            if !handle.is_finished() {
+                self.thinking = Some((at, handle));
+            } else if let Ok((opponent, decision)) = handle.join() {
+                // Dropped if the board changed while it was thinking
+                if at == self.current && self.state == State::Idle {
+                    self.opponent = Some(opponent);
+                    if let Some(decision) = decision {
+                        let (from, _, to) = decision.jump;
+                        let board = self.tree.get(self.current).expect("Exists").board;
+                        let new = board.apply_move(from, to).expect("Valid by construction");
+                        self.current = self.tree.push(self.current, new).1;
+                        if let Some(outcome) = decision.outcome() {
+                            println!("Computer jumps {:?} to {:?}, a {}", from, to, outcome);
+                        }
+                    }
+                }
+            }
+        }
+        if let (None, Some(opponent)) = (&self.thinking, &self.opponent) {
+            let moved = self.tree.depth(self.start, self.current).unwrap_or(0);
+            let board = self.tree.get(self.current).expect("Exists").board;
+            if self.state == State::Idle && moved % 2 == 1 && board.all_valid_moves().next().is_some() {
+                // The search keeps its own copy of the table and hands it back with the jump
+                let mut opponent = opponent.clone();
+                let sender = helper.create_user_event_sender();
+                let handle = thread::spawn(move || {
+                    let decision = opponent.choose(&board);
+                    // Fails only once the window is closed
+                    let _ = sender.send_event(());
+                    (opponent, decision)
+                });
+                self.thinking = Some((self.current, handle));
+            }
+        }
+
+        if let Some(shown) = self.counting.as_mut() {
+            let tree = &self.tree;
+            let moves = tree
+                .path(self.start, self.current)
+                .and_then(|path| {
+                    let boards = path
+                        .iter()
+                        .map(|&i| tree.get(i).expect("On the path").board)
+                        .collect_vec();
+                    moves::count_boards(&boards)
+                })
+                .unwrap_or(0);
+            if *shown != moves {
+                *shown = moves;
+                helper.set_title(&format!("{} moves", moves));
+            }
+        }
+
+        // Score the game once it is played out, solitaire only
+        if self.state != State::SelectStart && self.versus.is_none() {
+            let start = self.tree.get(self.start).expect("Exists").board;
+            let board = self.tree.get(self.current).expect("Exists").board;
+            if let Some(session) = self.tally.finish(&self.name, &start, &board) {
+                let best = score::personal_bests(&score::history())
+                    .into_iter()
+                    .find(|best| best.board == session.board && best.start == session.start)
+                    .map(score::Session::score);
+                println!("{}", cli::session(&session));
+                match best {
+                    Some(best) if best >= session.score() => println!("Personal best {}", best),
+                    _ => println!("New personal best"),
+                }
+                helper.set_title(&format!("{} points, {}", session.score(), session.rating()));
+                if let Err(e) = score::record(&session) {
+                    println!("Couldn't save to {}: {}", score::HISTORY_FILE, e);
+                }
+            }
+        }
+
+        // Log the daily challenge once it is played out from its start
+        if let Some(attempt) = self.daily.as_mut() {
+            let board = self.tree.get(self.current).expect("Exists").board;
+            let from_start = self.tree.get(self.start).map(|node| node.board)
+                == Some(attempt.challenge.start)
+                && self.tree.path(self.start, self.current).is_some();
+            if let Some(entry) = from_start.and_option_from(|| attempt.finish(&board)) {
+                println!(
+                    "Daily challenge over with {} stones{}",
+                    entry.stones,
+                    if entry.solved { ", solved" } else { "" }
+                );
+                if let Err(e) = daily::record(&entry) {
+                    println!("Couldn't log to {}: {}", daily::LOG_FILE, e);
+                }
+            }
+        }
+
+        if let Goal::Hole(hole) = self.goal {
+            graphics.draw_circle(
+                stone_pos::<G>(hole),
+                STONE_RADIUS * 1.2,
+                Color::from_rgb(1.0, 0.6, 0.0),
+            );
+        }
+
+        for pos in Board::<G>::iter_all() {
+            let stone = stone_pos::<G>(pos);
             let hover = hover.map(|(s, _)| s == pos).unwrap_or(false);
             let empty = !board.at(pos).expect("Must be valid position");
             let (pickup, drop) = match self.state {
@@ -252,6 +823,8 @@ impl WindowHandler for MyWindowHandler {
                     }
                 } else if empty {
                     Color::from_rgb(0.8, 0.9, 1.0)
+                } else if G::COLOURS > 1 {
+                    peg_colour(board.colour_at(pos).expect("Not empty"))
                 } else {
                     if pos.x % 2 == 0 && pos.y % 2 == 0 {
                         Color::from_rgb(1.0, 0.4023, 0.7)
@@ -262,6 +835,47 @@ impl WindowHandler for MyWindowHandler {
             );
         }
 
+        for pos in Board::<G>::iter_blocked() {
+            graphics.draw_circle(stone_pos::<G>(pos), STONE_RADIUS, Color::DARK_GRAY);
+            graphics.draw_circle(stone_pos::<G>(pos), STONE_RADIUS * 0.8, Color::BLACK);
+        }
+
+        // Holes the last stone can never end up in
+        if self.state != State::SelectStart {
+            for pos in Board::<G>::iter_all().filter(|&p| !board.may_reach(&Board::single(p))) {
+                graphics.draw_circle(stone_pos::<G>(pos), STONE_RADIUS / 6., Color::DARK_GRAY);
+            }
+        }
+
+        if self.estimating && self.estimates.as_ref().map_or(true, |&(b, _)| b != board) {
+            let estimates = strategy::estimates(&board, &self.goal, ROLLOUTS, 0);
+            self.estimates = Some((board, estimates));
+        }
+        let estimates = self
+            .estimates
+            .as_ref()
+            .filter(|_| self.estimating)
+            .map(|(_, estimates)| estimates.as_slice())
+            .unwrap_or(&[]);
+        let best_estimate = estimates.iter().map(|&(_, p)| p).fold(0., f64::max);
+        if self.hinting {
+            let goal = self.goal;
+            let (hints, hinted, cached) = self
+                .hints
+                .get_or_insert_with(|| (Hints::new(goal), board, Vec::new()));
+            if *hinted != board || cached.is_empty() {
+                *cached = hints.hints(&board);
+                *hinted = board;
+            }
+        }
+        let hints = self
+            .hints
+            .as_ref()
+            .filter(|_| self.hinting)
+            .map(|(_, _, hints)| hints.as_slice())
+            .unwrap_or(&[]);
+        let fewest = hints.iter().map(|h| h.fewest).min();
+
         // Helpful mode
         for (from, over, to) in board.all_valid_moves() {
             if let State::PickUpStone(pickup) = self.state {
@@ -269,13 +883,79 @@ impl WindowHandler for MyWindowHandler {
                     continue;
                 }
             }
-            let fromp = stone_pos(from);
-            let overp = stone_pos(over);
-            let top = stone_pos(to);
+            let fromp = stone_pos::<G>(from);
+            let overp = stone_pos::<G>(over);
+            let top = stone_pos::<G>(to);
             graphics.draw_circle(fromp, STONE_RADIUS * 3. / 5., Color::YELLOW);
             graphics.draw_circle(fromp, STONE_RADIUS / 3., Color::TRANSPARENT);
             graphics.draw_circle(overp, STONE_RADIUS / 3., Color::CYAN);
-            graphics.draw_circle(top, STONE_RADIUS / 2., Color::GREEN);
+            graphics.draw_circle(
+                top,
+                STONE_RADIUS / 2.,
+                match (&outcomes, hints.iter().find(|h| h.jump == (from, over, to))) {
+                    // Wins, loses, not solved yet
+                    (Some(outcomes), _) => {
+                        match outcomes.iter().find(|o| o.0 == (from, over, to)) {
+                            Some((_, Some(Outcome::Win))) => Color::GREEN,
+                            Some((_, Some(Outcome::Loss))) => Color::RED,
+                            _ => Color::from_rgb(1.0, 0.6, 0.0),
+                        }
+                    }
+                    // Keeps the goal, as good as it gets without the goal, worse
+                    (None, Some(hint)) if hint.solvable => Color::GREEN,
+                    (None, Some(hint)) if Some(hint.fewest) == fewest => Color::from_rgb(1.0, 0.6, 0.0),
+                    (None, Some(_)) => Color::RED,
+                    (None, None) => Color::GREEN,
+                },
+            );
+            // Between the stone jumped and the landing spot, greener is likelier to succeed
+            if let Some(&(_, p)) = estimates.iter().find(|&&(jump, _)| jump == (from, over, to)) {
+                let rel = if best_estimate > 0. { (p / best_estimate) as f32 } else { 0. };
+                graphics.draw_circle(
+                    (overp + top) / 2.,
+                    STONE_RADIUS / 4. * (0.5 + rel),
+                    Color::from_rgb(1. - rel, rel, 0.),
+                );
+            }
+        }
+
+        // The jump that lost the goal and what would have kept it
+        if let Some(analysis) = &self.analysis {
+            if let Some(blunder) = &analysis.blunder {
+                if analysis.steps[blunder.step].board == board {
+                    for &(from, _, to) in &blunder.alternatives {
+                        let (from, to) = (stone_pos::<G>(from), stone_pos::<G>(to));
+                        graphics.draw_line(from, to, 6., Color::GREEN);
+                    }
+                    let (from, _, to) = blunder.jump;
+                    let (from, to) = (stone_pos::<G>(from), stone_pos::<G>(to));
+                    graphics.draw_line(from, to, 6., Color::RED);
+                }
+            }
+        }
+
+        // The analysis report beside the board, the line of the board shown in blue
+        if let Some(analysis) = &self.analysis {
+            let (steps, blunder) = (&analysis.steps, analysis.blunder.as_ref());
+            let alternatives = blunder.map_or(0, |blunder| blunder.alternatives.len());
+            let left = board_size::<G>().x;
+            for (i, line) in analysis.report().iter().enumerate() {
+                let colour = match steps.get(i) {
+                    Some(step) if step.board == board => Color::BLUE,
+                    Some(_) if blunder.map(|blunder| blunder.step) == Some(i) => Color::RED,
+                    Some(step) if step.solvable => Color::BLACK,
+                    Some(_) => Color::DARK_GRAY,
+                    // The blunder, then what would have kept the goal, then the summary
+                    None if blunder.is_some() && i == steps.len() => Color::RED,
+                    None if i <= steps.len() + alternatives && blunder.is_some() => {
+                        Color::from_rgb(0.0, 0.5, 0.0)
+                    }
+                    None => Color::BLACK,
+                };
+                let text = self.font.layout_text(line, REPORT_TEXT_SIZE, TextOptions::new());
+                let top = REPORT_TEXT_SIZE * 1.4 * i as f32 + REPORT_TEXT_SIZE;
+                graphics.draw_text((left, top.round()), colour, &text);
+            }
         }
 
         graphics.draw_circle(
@@ -339,18 +1019,164 @@ impl WindowHandler for MyWindowHandler {
     }
 }
 
-fn stone_pos(pos: Position) -> Vector2<f32> {
-    let x = pos.x as f32;
-    let y = pos.y as f32;
+fn peg_colour(colour: u8) -> Color {
+    match colour {
+        0 => Color::BLUE,
+        1 => Color::from_rgb(1.0, 0.4023, 0.7),
+        2 => Color::from_rgb(0.5, 0.1, 0.8),
+        _ => Color::from_rgb(0.6, 0.4, 0.1),
+    }
+}
+
+/// Room the board takes up in the window
+fn board_size<G: Geometry>() -> Vector2<f32> {
+    Board::<G>::iter_all()
+        .chain(Board::<G>::iter_blocked())
+        .map(stone_pos::<G>)
+        .fold(Vector2::<f32>::ZERO, |size, p| {
+            Vector2::new(size.x.max(p.x), size.y.max(p.y))
+        })
+        + Vector2::new(STONE_RADIUS * 2., STONE_RADIUS * 2.)
+}
+/// Window size in pixels, with room beside the board for the analysis report while it is shown
+fn window_size<G: Geometry>(report: bool) -> (u32, u32) {
+    let size = board_size::<G>() + Vector2::new(if report { REPORT_WIDTH } else { 0. }, 0.);
+    (size.x as u32, size.y as u32)
+}
+
+fn stone_pos<G: Geometry>(pos: Position) -> Vector2<f32> {
+    let (x, y) = G::layout(pos);
     let stone = Vector2::new(
-        x * STONE_RADIUS * 4. + STONE_RADIUS * 2. + y * STONE_RADIUS * 2.,
-        STONE_RADIUS * 2. + y * STONE_RADIUS * 4. * f32::cos(PI / 6.),
+        x * STONE_RADIUS * 4. + STONE_RADIUS * 2.,
+        y * STONE_RADIUS * 4. + STONE_RADIUS * 2.,
     );
     stone
 }
 
-fn main() {
-    let window = Window::new_centered("Title", (640, 480)).unwrap();
+fn run<G: Geometry>() {
+    let hole = match cli::flag("hole") {
+        Some(hole) => match Position::parse(&hole).filter(|&p| Board::<G>::get_idx(p).is_some()) {
+            Some(hole) => hole,
+            None => {
+                println!("--hole=x,y, a cell of the board");
+                return;
+            }
+        },
+        None => Board::<G>::iter_all().next().expect("Board has cells"),
+    };
+    if let Some(games) = cli::flag("strategies") {
+        cli::strategies(Board::<G>::start(hole), games.parse().unwrap_or(1000));
+        return;
+    }
+    if let Some(count) = cli::flag("puzzles") {
+        let cells = Board::<G>::iter_all().count();
+        cli::puzzles::<G>(
+            cli::flag("stones")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(cells / 3),
+            count.parse().unwrap_or(10),
+            cli::flag("solutions")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(1),
+            cli::flag("seed").and_then(|s| s.parse().ok()).unwrap_or(0),
+        );
+        return;
+    }
+    if let Some(holes) = cli::flag("starts") {
+        cli::starts(starts::starts::<G>(holes.parse().unwrap_or(1)));
+        return;
+    }
+    if let Some(shape) = cli::flag("shape") {
+        match shape.split(';').map(Position::parse).collect::<Option<Vec<_>>>() {
+            Some(shape) => cli::starts(starts::shape_starts::<G>(&shape)),
+            None => println!("--shape=x,y;x,y;..."),
+        }
+        return;
+    }
+    if let Some(date) = cli::flag("daily") {
+        cli::daily::<G>(date.parse().unwrap_or_else(|_| Date::today()));
+        return;
+    }
+    if let Some(limit) = cli::flag("enumerate") {
+        let start = Board::<G>::start(hole);
+        let goal = match cli::goal_flag(&start) {
+            Some(goal) => goal,
+            None => return,
+        };
+        let dedup = solutions::Dedup {
+            symmetry: cli::flag("symmetry").is_some(),
+            commuting: cli::flag("commuting").is_some(),
+        };
+        cli::enumerate(start, goal, dedup, limit.parse().ok());
+        return;
+    }
+    if cli::flag("packages").is_some() {
+        let start = Board::<G>::start(hole);
+        if let Some(goal) = cli::goal_flag(&start) {
+            cli::macro_solution(start, goal);
+        }
+        return;
+    }
+    if cli::flag("history").is_some() {
+        cli::history();
+        return;
+    }
+    if let Some(paths) = cli::flag("reach") {
+        match paths.split(',').collect_tuple() {
+            Some((from, to)) => cli::reach::<G>(from, to),
+            None => println!("--reach=from.txt,to.txt"),
+        }
+        return;
+    }
+    if let Some(path) = cli::flag("record") {
+        cli::record::<G>(&path);
+        return;
+    }
+    if let Some(path) = cli::flag("rate") {
+        cli::rate::<G>(&path);
+        return;
+    }
+    if cli::flag("cli").is_some() {
+        let play = if cli::flag("misere").is_some() {
+            Play::Misere
+        } else {
+            Play::Normal
+        };
+        let strength = cli::flag("strength")
+            .and_then(|s| Strength::from_name(&s))
+            .unwrap_or(Strength::Hard);
+        cli::versus(Board::<G>::start(hole), play, strength, cli::flag("second").is_none());
+        return;
+    }
+    let window = Window::new_centered("Title", window_size::<G>(false)).unwrap();
 
-    window.run_loop(MyWindowHandler::new())
+    window.run_loop(MyWindowHandler::<G>::new())
+}
+
+fn run_rules<G: Geometry>() {
+    match cli::arg(1).as_deref() {
+        Some("diagonal") => run::<Variant<G, Diagonal>>(),
+        Some("orthogonal") => run::<Variant<G, Orthogonal>>(),
+        Some("long") => run::<Variant<G, Long>>(),
+        Some("colours") => run::<Variant<G, Colours<2>>>(),
+        Some("contrast") => run::<Variant<G, OtherColour<2>>>(),
+        Some("kin") => run::<Variant<G, SameColour<2>>>(),
+        _ => run::<G>(),
+    }
+}
+
+fn main() {
+    match cli::arg(0).as_deref() {
+        Some("english") => run_rules::<English>(),
+        Some("french") => run_rules::<French>(),
+        Some("diamond") => run_rules::<Diamond<4>>(),
+        Some("square") => run_rules::<Square<6>>(),
+        Some("hexagon") => run_rules::<Hexagon<3>>(),
+        Some("star") => run_rules::<Star<2>>(),
+        // Triangle without its corners
+        Some("notched") => run_rules::<Masked<Triangle<6>, 0x100021>>(),
+        // Hexagon with the centre blocked
+        Some("ring") => run_rules::<Masked<Hexagon<3>, { 1 << 18 }>>(),
+        _ => run_rules::<Triangle<BOARD_SIZE>>(),
+    }
 }
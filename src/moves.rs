@@ -0,0 +1,131 @@
+use fxhash::FxHashMap;
+use itertools::Itertools;
+
+use crate::{
+    board::{Board, Position},
+    geometry::Geometry,
+    goal::Goal,
+    solver::Solver,
+    strategy::Jump,
+};
+
+/// Consecutive jumps by the same stone, one move
+pub type Chain = Vec<Jump>;
+
+/// Number of moves in a sequence of jumps, a jump from where the last one landed is free
+pub fn count(jumps: &[Jump]) -> usize {
+    split(jumps).len()
+}
+
+/// Group jumps into moves
+pub fn split(jumps: &[Jump]) -> Vec<Chain> {
+    jumps.iter().fold(Vec::<Chain>::new(), |mut chains, &jump| {
+        let (from, _, _) = jump;
+        match chains.last_mut() {
+            Some(chain) if chain.last().map(|&(_, _, to)| to) == Some(from) => chain.push(jump),
+            _ => chains.push(vec![jump]),
+        }
+        chains
+    })
+}
+
+/// Moves made between consecutive boards of a game, None if one doesn't follow from the last
+pub fn count_boards<G: Geometry>(boards: &[Board<G>]) -> Option<usize> {
+    let jumps = boards
+        .iter()
+        .tuple_windows()
+        .map(|(board, next)| board.jump_to(next))
+        .collect::<Option<Vec<_>>>()?;
+    Some(count(&jumps))
+}
+
+/// Fewest moves reaching the goal: IDA* over (board, stone that jumped last)
+#[derive(Debug, Clone)]
+pub struct MoveSolver<G: Geometry> {
+    solver: Solver<G>,
+    /// Cells no jump goes over, a stone there only leaves by jumping itself
+    corners: Vec<Position>,
+    /// Most moves already known not to be enough
+    failed: FxHashMap<(Board<G>, Option<Position>), usize>,
+}
+
+impl<G: Geometry> MoveSolver<G> {
+    pub fn new(goal: Goal<G>) -> Self {
+        let overs = Board::<G>::jumps().map(|(_, over, _)| over).collect_vec();
+        Self {
+            solver: Solver::new(goal),
+            corners: Board::<G>::iter_all()
+                .filter(|p| !overs.contains(p))
+                .collect(),
+            failed: FxHashMap::default(),
+        }
+    }
+    pub fn goal(&self) -> &Goal<G> {
+        self.solver.goal()
+    }
+
+    /// Never more moves than are needed: every stone in a corner but the ones the goal leaves
+    /// standing has to start a move of its own, and the one that jumped last can carry on for
+    /// free. Goals that may leave any number of stones give no bound
+    fn lower_bound(&self, board: &Board<G>, last: Option<Position>) -> usize {
+        let left = match *self.goal() {
+            Goal::AnyOne | Goal::Hole(_) | Goal::Colour(_) => 1,
+            Goal::AtMost(n) => n,
+            Goal::Pattern(target) => target.count(),
+            Goal::Stuck(_) | Goal::Custom(_) => return 0,
+        };
+        let stuck = self
+            .corners
+            .iter()
+            .filter(|&&p| board.at(p) == Some(true))
+            .count();
+        let free = left + last.map_or(0, |p| self.corners.contains(&p) as usize);
+        stuck.saturating_sub(free)
+    }
+
+    /// A solution with the fewest moves, None if the goal can't be reached
+    pub fn solve(&mut self, board: &Board<G>) -> Option<Vec<Chain>> {
+        if !self.solver.solvable(board) {
+            return None;
+        }
+        let mut path = Vec::with_capacity(board.count());
+        for budget in self.lower_bound(board, None).. {
+            if self.search(board, None, budget, &mut path) {
+                return Some(split(&path));
+            }
+        }
+        unreachable!("A solvable board is solved within as many moves as it has jumps")
+    }
+    fn search(
+        &mut self,
+        board: &Board<G>,
+        last: Option<Position>,
+        budget: usize,
+        path: &mut Vec<Jump>,
+    ) -> bool {
+        if self.solver.goal().reached(board) {
+            return true;
+        }
+        if self.lower_bound(board, last) > budget
+            || self.failed.get(&(*board, last)).map_or(false, |&b| b >= budget)
+            || !self.solver.solvable(board)
+        {
+            return false;
+        }
+        for jump in board.all_valid_moves().collect_vec() {
+            let (from, _, to) = jump;
+            let cost = (Some(from) != last) as usize;
+            if cost > budget {
+                continue;
+            }
+            let next = board.apply_move(from, to).expect("Valid by construction");
+            path.push(jump);
+            if self.search(&next, Some(to), budget - cost, path) {
+                return true;
+            }
+            path.pop();
+        }
+        self.failed.insert((*board, last), budget);
+        false
+    }
+}
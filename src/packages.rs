@@ -0,0 +1,217 @@
+use std::{convert::TryFrom, fmt};
+
+use itertools::Itertools;
+
+use crate::{
+    board::{Board, Position},
+    geometry::{Geometry, HEX_DIRECTIONS},
+    solver::Solver,
+    strategy::Jump,
+};
+
+/// Step on the triangular lattice, in the same axes as `Position`
+pub type Offset = (i8, i8);
+
+/// A short sequence of jumps that clears a pattern of stones and leaves every other cell it uses
+/// as it found it. Every jump removes a stone, so the pattern always has one stone of each of the
+/// lattice's three colours for every three stones, that rules out bent threes and any fours
+#[derive(Debug, PartialEq, Eq)]
+pub struct Package {
+    pub name: &'static str,
+    /// (from, to) of each jump, from the anchor
+    pub jumps: &'static [(Offset, Offset)],
+}
+
+/// Packages of the triangular lattice
+pub const PACKAGES: &[Package] = &[
+    // Three in a line, with a stone and a hole either side of the middle of it
+    Package {
+        name: "3-purge",
+        jumps: &[((-1, 1), (1, -1)), ((2, 0), (0, 0)), ((1, -1), (-1, 1))],
+    },
+    // Three touching each other
+    Package {
+        name: "triangle purge",
+        jumps: &[((-1, 1), (1, 1)), ((1, -1), (-1, 1)), ((1, 1), (1, -1))],
+    },
+    // Two lines of three meeting at 60 degrees, a 3-purge for each
+    Package {
+        name: "L-purge",
+        jumps: &[
+            ((-1, 1), (1, 1)),
+            ((0, 3), (0, 1)),
+            ((1, 1), (-1, 1)),
+            ((-1, 1), (1, -1)),
+            ((2, 0), (0, 0)),
+            ((1, -1), (-1, 1)),
+        ],
+    },
+    // Two lines of three side by side, the first one's purge opens the hole for the second
+    Package {
+        name: "6-purge",
+        jumps: &[
+            ((-1, 1), (1, -1)),
+            ((2, 0), (0, 0)),
+            ((1, -1), (-1, 1)),
+            ((-1, 2), (1, 0)),
+            ((2, 1), (0, 1)),
+            ((1, 0), (-1, 2)),
+        ],
+    },
+];
+
+fn over((fx, fy): Offset, (tx, ty): Offset) -> Offset {
+    ((fx + tx) / 2, (fy + ty) / 2)
+}
+
+/// The twelve symmetries of the lattice around the anchor: `t % 6` turns of 60 degrees, then
+/// a reflection if `t >= 6`
+fn transform(t: usize, offset: Offset) -> Offset {
+    let (dx, dy) = (0..t % 6).fold(offset, |(dx, dy), _| (-dy, dx + dy));
+    if t >= 6 {
+        (dy, dx)
+    } else {
+        (dx, dy)
+    }
+}
+
+impl Package {
+    /// Whether each cell the package uses needs a stone before it starts
+    pub fn requires(&self) -> Vec<(Offset, bool)> {
+        let mut required = Vec::<(Offset, bool)>::new();
+        for &(from, to) in self.jumps {
+            for &(cell, stone) in &[(from, true), (over(from, to), true), (to, false)] {
+                if !required.iter().any(|&(c, _)| c == cell) {
+                    required.push((cell, stone));
+                }
+            }
+        }
+        required
+    }
+    /// Cells that have a stone before and none after, the rest end up as they were
+    pub fn clears(&self) -> Vec<Offset> {
+        self.requires()
+            .into_iter()
+            .filter(|&(_, stone)| stone)
+            .map(|(cell, _)| cell)
+            .filter(|&cell| {
+                // A cell's last use decides its final state: it was either jumped from or over,
+                // or landed on
+                self.jumps
+                    .iter()
+                    .rev()
+                    .find_map(|&(from, to)| {
+                        if to == cell {
+                            Some(false)
+                        } else if from == cell || over(from, to) == cell {
+                            Some(true)
+                        } else {
+                            None
+                        }
+                    })
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+}
+
+/// A package at one place and orientation on a board
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placement {
+    pub package: &'static Package,
+    pub jumps: Vec<Jump>,
+    pub clears: Vec<Position>,
+}
+
+impl Placement {
+    pub fn apply<G: Geometry>(&self, board: &Board<G>) -> Option<Board<G>> {
+        self.jumps
+            .iter()
+            .try_fold(*board, |board, &(from, _, to)| board.apply_move(from, to))
+    }
+}
+
+impl fmt::Display for Placement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let clears = self.clears.iter().map(|p| (p.x, p.y)).collect_vec();
+        write!(f, "apply a {} here, clearing {:?}", self.package.name, clears)
+    }
+}
+
+/// Every place a package can be played on `board`, under every symmetry of the lattice.
+/// Boards on other lattices have none
+pub fn matches<G: Geometry>(board: &Board<G>) -> Vec<Placement> {
+    if G::DIRECTIONS != HEX_DIRECTIONS {
+        return Vec::new();
+    }
+    let at = |anchor: Position, offset: Offset| -> Option<Position> {
+        Position::try_from((
+            anchor.x as i16 + offset.0 as i16,
+            anchor.y as i16 + offset.1 as i16,
+        ))
+        .ok()
+        .filter(|&p| Board::<G>::get_idx(p).is_some())
+    };
+    Board::<G>::iter_all()
+        .cartesian_product(0..12)
+        .flat_map(|(anchor, t)| PACKAGES.iter().map(move |package| (anchor, t, package)))
+        .filter_map(|(anchor, t, package)| {
+            let place = |offset| at(anchor, transform(t, offset));
+            let ready = package
+                .requires()
+                .into_iter()
+                .all(|(offset, stone)| place(offset).and_then(|p| board.at(p)) == Some(stone));
+            if !ready {
+                return None;
+            }
+            let jumps = package
+                .jumps
+                .iter()
+                .map(|&(from, to)| Some((place(from)?, place(over(from, to))?, place(to)?)))
+                .collect::<Option<Vec<_>>>()?;
+            let placement = Placement {
+                package,
+                jumps,
+                clears: package.clears().into_iter().filter_map(place).sorted().collect(),
+            };
+            // Colour rules can still forbid one of the jumps
+            placement.apply(board).map(|_| placement)
+        })
+        .unique_by(|placement| (placement.package.name, placement.jumps.clone()))
+        .collect()
+}
+
+/// A jump or a package played as one move
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Macro {
+    Jump(Jump),
+    Package(Placement),
+}
+
+impl Macro {
+    pub fn jumps(&self) -> &[Jump] {
+        match self {
+            Macro::Jump(jump) => std::slice::from_ref(jump),
+            Macro::Package(placement) => &placement.jumps,
+        }
+    }
+}
+
+impl fmt::Display for Macro {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Macro::Jump((from, _, to)) => {
+                write!(f, "jump ({}, {}) to ({}, {})", from.x, from.y, to.x, to.y)
+            }
+            Macro::Package(placement) => write!(f, "{}", placement),
+        }
+    }
+}
+
+/// Packages that keep the solver's goal in reach from `board`
+pub fn hints<G: Geometry>(solver: &mut Solver<G>, board: &Board<G>) -> Vec<Placement> {
+    matches(board)
+        .into_iter()
+        .filter(|placement| solver.solvable(&placement.apply(board).expect("Matched")))
+        .collect()
+}
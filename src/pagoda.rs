@@ -0,0 +1,177 @@
+use std::{collections::VecDeque, marker::PhantomData};
+
+use boolinator::Boolinator;
+use itertools::Itertools;
+
+use crate::{
+    board::{BitSetType, Board, Position},
+    geometry::Geometry,
+    goal::Goal,
+};
+
+const MAX_CELLS: usize = BitSetType::BITS as usize;
+
+/// A pagoda function: cell weights such that `w(to) <= w(from) + w(over)` for every jump,
+/// so the weighted sum of the stones on a board can never increase
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pagoda<G: Geometry> {
+    /// Indexed by `Board::get_idx`
+    weights: Vec<i64>,
+    geometry: PhantomData<G>,
+}
+
+impl<G: Geometry> Pagoda<G> {
+    /// Weights given in `Board::iter_all` order, None if they do not form a pagoda function
+    pub fn new(weights: impl IntoIterator<Item = i64>) -> Option<Self> {
+        let mut w = vec![0; MAX_CELLS];
+        for (p, weight) in Board::<G>::iter_all().zip(weights) {
+            w[Board::<G>::get_idx(p).expect("Inbounds by construction")] = weight;
+        }
+        let pagoda = Self {
+            weights: w,
+            geometry: PhantomData,
+        };
+        pagoda.is_valid().as_some(pagoda)
+    }
+    /// Fibonacci weights falling off with the distance from `target`.
+    /// Moving towards the target keeps the value, anything else loses some of it
+    pub fn towards(target: Position) -> Self {
+        let dist = distances::<G>(target);
+        let far = dist.iter().flatten().copied().max().unwrap_or(0);
+        let fib = iter_fib().take(far + 3).collect_vec();
+        let weights = dist.into_iter().map(|d| d.map(|d| fib[far + 2 - d]).unwrap_or(0));
+        Self::new(weights).expect("Fibonacci weights on graph distance form a pagoda function")
+    }
+
+    pub fn is_valid(&self) -> bool {
+        Board::<G>::jumps().all(|(from, over, to)| {
+            self.weight(to) <= self.weight(from) + self.weight(over)
+        })
+    }
+    pub fn weight(&self, p: Position) -> i64 {
+        Board::<G>::get_idx(p)
+            .map(|i| self.weights[i])
+            .unwrap_or(0)
+    }
+    pub fn value(&self, board: &Board<G>) -> i64 {
+        board.iter_stones().map(|p| self.weight(p)).sum()
+    }
+    /// Change of `value` caused by the jump, never positive
+    pub fn delta(&self, from: Position, over: Position, to: Position) -> i64 {
+        self.weight(to) - self.weight(from) - self.weight(over)
+    }
+}
+
+fn iter_fib() -> impl Iterator<Item = i64> {
+    itertools::unfold((0, 1), |(a, b)| {
+        let ret = *a;
+        *a = *b;
+        *b += ret;
+        Some(ret)
+    })
+}
+
+/// Graph distance from `from` using single steps, in `Board::iter_all` order
+fn distances<G: Geometry>(from: Position) -> Vec<Option<usize>> {
+    let cells = Board::<G>::iter_all().collect_vec();
+    let mut dist = vec![None; cells.len()];
+    let idx = |p: Position| cells.iter().position(|&c| c == p);
+    let mut qu = VecDeque::new();
+    if let Some(i) = idx(from) {
+        dist[i] = Some(0);
+        qu.push_back(from);
+    }
+    while let Some(p) = qu.pop_front() {
+        let d = dist[idx(p).expect("Queued cells are on the board")].expect("Queued cells have a distance");
+        for n in Board::<G>::adjacent(p) {
+            let i = idx(n).expect("Adjacent cells are on the board");
+            if dist[i].is_none() {
+                dist[i] = Some(d + 1);
+                qu.push_back(n);
+            }
+        }
+    }
+    dist
+}
+
+/// Pagoda functions towards the stones of each candidate finishing board, used to prove
+/// a board can't reach any of them
+#[derive(Debug, Clone)]
+pub struct Pagodas<G: Geometry> {
+    pagodas: Vec<Pagoda<G>>,
+    /// Candidate finishes with their value under each pagoda, None means anything goes
+    targets: Option<Vec<(Board<G>, Vec<i64>)>>,
+}
+
+impl<G: Geometry> Default for Pagodas<G> {
+    fn default() -> Self {
+        Self::for_goal(&Goal::AnyOne, &Board::full())
+    }
+}
+
+impl<G: Geometry> Pagodas<G> {
+    /// The finishes of `goal` in the same position class as `board`
+    pub fn for_goal(goal: &Goal<G>, board: &Board<G>) -> Self {
+        let class = board.position_class();
+        match goal.targets() {
+            // Pagodas only bound single jumps
+            Some(targets) if !G::LONG_JUMPS => {
+                Self::for_targets(targets.into_iter().filter(|t| t.position_class() == class))
+            }
+            _ => Self {
+                pagodas: Vec::new(),
+                targets: None,
+            },
+        }
+    }
+    pub fn for_targets(targets: impl IntoIterator<Item = Board<G>>) -> Self {
+        let targets = targets.into_iter().collect_vec();
+        let pagodas = targets
+            .iter()
+            .flat_map(|t| t.iter_stones())
+            .unique()
+            .map(Pagoda::towards)
+            .collect_vec();
+        let targets = targets
+            .into_iter()
+            .map(|t| (t, pagodas.iter().map(|pg| pg.value(&t)).collect()))
+            .collect();
+        Self {
+            pagodas,
+            targets: Some(targets),
+        }
+    }
+
+    /// Value of the board under each pagoda, to be kept up to date with `apply`
+    pub fn values(&self, board: &Board<G>) -> Vec<i64> {
+        self.pagodas.iter().map(|pg| pg.value(board)).collect()
+    }
+    pub fn apply(&self, values: &mut [i64], from: Position, over: Position, to: Position) {
+        for (pg, v) in self.pagodas.iter().zip(values) {
+            *v += pg.delta(from, over, to);
+        }
+    }
+    /// Candidate finishes not ruled out for a board with `stones` stones and these `values`,
+    /// None if there is no list of candidates
+    pub fn finishes<'a>(
+        &'a self,
+        values: &'a [i64],
+        stones: usize,
+    ) -> Option<impl Iterator<Item = &'a Board<G>> + 'a> {
+        Some(
+            self.targets
+                .as_ref()?
+                .iter()
+                .filter(move |(t, tv)| {
+                    t.count() <= stones && values.iter().zip(tv).all(|(v, tv)| v >= tv)
+                })
+                .map(|(t, _)| t),
+        )
+    }
+    /// false means the board provably can't reach the goal
+    pub fn finishable(&self, values: &[i64], stones: usize) -> bool {
+        self.finishes(values, stones)
+            .map(|mut it| it.next().is_some())
+            .unwrap_or(true)
+    }
+}
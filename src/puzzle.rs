@@ -0,0 +1,210 @@
+use std::fmt;
+
+use fxhash::{FxHashMap, FxHashSet};
+use itertools::Itertools;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{board::Board, geometry::Geometry, goal::Goal, solver::Solver};
+
+/// Random backward games tried per puzzle asked for before giving up
+const ATTEMPTS: usize = 100;
+
+/// How hard a puzzle is to solve by hand
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Difficulty {
+    /// Jumps in every solution
+    pub depth: usize,
+    /// Mean number of different jumps, up to symmetry, from the boards on some solution
+    pub branching: f64,
+    /// Share of those jumps after which the puzzle can't be solved any more
+    pub dead_ends: f64,
+}
+
+impl Difficulty {
+    /// Longer solutions are harder, more choices only count as much as they lead astray
+    pub fn score(&self) -> f64 {
+        self.depth as f64 * (1. + self.branching * self.dead_ends)
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "difficulty {:.1}: {} jumps, {:.1} choices per board, {:.0}% dead ends",
+            self.score(),
+            self.depth,
+            self.branching,
+            100. * self.dead_ends
+        )
+    }
+}
+
+/// A board to play down to a single stone
+#[derive(Debug, Copy, Clone)]
+pub struct Puzzle<G: Geometry> {
+    pub board: Board<G>,
+    /// Different sequences of boards reaching one stone, boards that are symmetries of each
+    /// other counting as the same
+    pub solutions: u64,
+    pub difficulty: Difficulty,
+}
+
+/// Header line then the board in the text notation
+impl<G: Geometry> fmt::Display for Puzzle<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} stones, {} solutions up to symmetry, {}",
+            self.board.count(),
+            self.solutions,
+            self.difficulty
+        )?;
+        write!(f, "{}", self.board)
+    }
+}
+
+/// Rates boards, remembers what it learns between them
+#[derive(Debug, Clone)]
+pub struct Rater<G: Geometry> {
+    solver: Solver<G>,
+    /// By canonical board
+    solutions: FxHashMap<Board<G>, u64>,
+}
+
+impl<G: Geometry> Default for Rater<G> {
+    fn default() -> Self {
+        Self {
+            solver: Solver::new(Goal::AnyOne),
+            solutions: FxHashMap::default(),
+        }
+    }
+}
+
+impl<G: Geometry> Rater<G> {
+    /// Canonical boards one jump away, each once
+    fn children(board: &Board<G>) -> Vec<Board<G>> {
+        board
+            .all_valid_moves()
+            .map(|(from, _, to)| board.apply_move(from, to).expect("Valid by construction"))
+            .map(|next| next.canonicalize())
+            .unique()
+            .collect()
+    }
+
+    /// Number of solutions up to symmetry
+    pub fn solutions(&mut self, board: &Board<G>) -> u64 {
+        if self.solver.goal().reached(board) {
+            return 1;
+        }
+        if !self.solver.solvable(board) {
+            return 0;
+        }
+        let key = board.canonicalize();
+        if let Some(&n) = self.solutions.get(&key) {
+            return n;
+        }
+        let n = Self::children(&key)
+            .iter()
+            .map(|next| self.solutions(next))
+            .fold(0, u64::saturating_add);
+        self.solutions.insert(key, n);
+        n
+    }
+
+    /// None if `board` can't be solved
+    pub fn rate(&mut self, board: &Board<G>) -> Option<Puzzle<G>> {
+        if !self.solver.solvable(board) {
+            return None;
+        }
+        // Every board some solution goes through
+        let start = board.canonicalize();
+        let mut seen = FxHashSet::default();
+        seen.insert(start);
+        let mut stack = vec![start];
+        let (mut boards, mut jumps, mut dead) = (0, 0, 0);
+        while let Some(next) = stack.pop() {
+            if self.solver.goal().reached(&next) {
+                continue;
+            }
+            let children = Self::children(&next);
+            boards += 1;
+            jumps += children.len();
+            for child in children {
+                if !self.solver.solvable(&child) {
+                    dead += 1;
+                } else if seen.insert(child) {
+                    stack.push(child);
+                }
+            }
+        }
+        Some(Puzzle {
+            board: *board,
+            solutions: self.solutions(board),
+            difficulty: Difficulty {
+                depth: board.count() - 1,
+                branching: jumps as f64 / boards.max(1) as f64,
+                dead_ends: dead as f64 / jumps.max(1) as f64,
+            },
+        })
+    }
+}
+
+/// Up to `count` different puzzles with `stones` stones and at most `max_solutions` solutions
+/// each, hardest first. Each comes from jumping backwards from a single stone, so all of them
+/// can be solved
+pub fn generate<G: Geometry>(
+    stones: usize,
+    count: usize,
+    max_solutions: u64,
+    seed: u64,
+) -> Vec<Puzzle<G>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut rater = Rater::default();
+    let singles = Board::<G>::iter_all().map(Board::single).collect_vec();
+    let mut tried = FxHashSet::default();
+    let mut puzzles = Vec::with_capacity(count);
+    for _ in 0..count * ATTEMPTS {
+        if puzzles.len() >= count {
+            break;
+        }
+        let mut board = match singles.choose(&mut rng) {
+            Some(&single) => single,
+            None => break,
+        };
+        // Keep the solutions few on the way, at random between the backward jumps that do
+        while board.count() < stones {
+            let options = board
+                .predecessors()
+                .into_iter()
+                .map(|(_, prev)| (rater.solutions(&prev), prev))
+                .collect_vec();
+            let fewest = options.iter().map(|&(n, _)| n).min();
+            let fewest = options
+                .into_iter()
+                .filter(|&(n, _)| Some(n) == fewest)
+                .map(|(_, prev)| prev)
+                .collect_vec();
+            match fewest.choose(&mut rng) {
+                Some(&prev) => board = prev,
+                None => break,
+            }
+        }
+        if board.count() < stones || !tried.insert(board.canonicalize()) {
+            continue;
+        }
+        if let Some(puzzle) = rater
+            .rate(&board)
+            .filter(|puzzle| puzzle.solutions <= max_solutions)
+        {
+            puzzles.push(puzzle);
+        }
+    }
+    puzzles.sort_by(|a, b| {
+        b.difficulty
+            .score()
+            .partial_cmp(&a.difficulty.score())
+            .expect("Scores are finite")
+    });
+    puzzles
+}
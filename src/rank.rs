@@ -0,0 +1,195 @@
+use std::marker::PhantomData;
+
+use bit_set::BitSet;
+use fxhash::FxHashSet;
+use itertools::Itertools;
+
+use crate::{
+    board::{Board, Position},
+    geometry::Geometry,
+};
+
+/// Perfect ranking of boards via the combinatorial number system.
+/// A board with `k` stones on `n` cells maps to a unique integer in `[0, C(n, k))`,
+/// with `c` colours that is scaled by `c^k` and the colours added as base `c` digits.
+/// Layers with more boards than fit in a `u64` have no ranks
+#[derive(Debug, Clone)]
+pub struct Ranker<G: Geometry> {
+    /// Cells in `Board::iter_all` order, the dense index of a cell is its index here
+    cells: Vec<Position>,
+    /// binom[n][k] = C(n, k)
+    binom: Vec<Vec<u64>>,
+    geometry: PhantomData<G>,
+}
+
+impl<G: Geometry> Default for Ranker<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<G: Geometry> Ranker<G> {
+    pub fn new() -> Self {
+        let cells = Board::<G>::iter_all().collect_vec();
+        let n = cells.len();
+        let mut binom = vec![vec![0u64; n + 2]; n + 2];
+        for i in 0..=n + 1 {
+            binom[i][0] = 1;
+            for k in 1..=i {
+                binom[i][k] = binom[i - 1][k - 1] + binom[i - 1][k];
+            }
+        }
+        Self {
+            cells,
+            binom,
+            geometry: PhantomData,
+        }
+    }
+    /// Number of cells on the board
+    pub fn cells(&self) -> usize {
+        self.cells.len()
+    }
+    /// Number of distinct boards with `pegs` stones, ranks are in `0..self.count(pegs)`.
+    /// None if there are too many to rank
+    pub fn count(&self, pegs: usize) -> Option<u64> {
+        self.binom(self.cells(), pegs).checked_mul(self.colourings(pegs)?)
+    }
+    /// Ways to colour `pegs` stones, consecutive ranks differ only in colour
+    pub fn colourings(&self, pegs: usize) -> Option<u64> {
+        (G::COLOURS as u64).checked_pow(pegs as u32)
+    }
+    fn binom(&self, n: usize, k: usize) -> u64 {
+        if k > n {
+            0
+        } else {
+            self.binom[n][k]
+        }
+    }
+
+    /// (number of stones, rank among boards with that many stones), None if there are too many
+    /// boards with that many stones to rank
+    pub fn rank(&self, board: &Board<G>) -> Option<(usize, u64)> {
+        let (k, r) = board
+            .iter()
+            .map(|(_, stone)| stone)
+            .enumerate()
+            .filter(|&(_, stone)| stone)
+            .fold((0, 0), |(k, r), (c, _)| (k + 1, r + self.binom(c, k + 1)));
+        let colourings = self.count(k).and(self.colourings(k))?;
+        if G::COLOURS == 1 {
+            return Some((k, r));
+        }
+        let colours = self
+            .cells
+            .iter()
+            .filter_map(|&p| board.colour_at(p))
+            .fold(0, |code, colour| code * G::COLOURS as u64 + colour as u64);
+        Some((k, r * colourings + colours))
+    }
+    /// Rank of the canonical representative, so all symmetric boards share a rank
+    pub fn rank_canonical(&self, board: &Board<G>) -> Option<(usize, u64)> {
+        self.rank(&board.canonicalize())
+    }
+}
+
+/// Layers with more boards than this are kept in a hash set instead of a flat bit array
+const DENSE_LIMIT: u64 = 1 << 26;
+
+#[derive(Debug, Clone)]
+enum Layer {
+    /// One bit per rank
+    Dense(BitSet),
+    /// Only the ranks present, for boards with too many cells for a flat array
+    Sparse(FxHashSet<u64>),
+}
+
+impl Layer {
+    fn new(count: u64) -> Self {
+        if count <= DENSE_LIMIT {
+            Layer::Dense(BitSet::with_capacity(count as usize))
+        } else {
+            Layer::Sparse(FxHashSet::default())
+        }
+    }
+    fn contains(&self, r: u64) -> bool {
+        match self {
+            Layer::Dense(bits) => bits.contains(r as usize),
+            Layer::Sparse(set) => set.contains(&r),
+        }
+    }
+    fn insert(&mut self, r: u64) -> bool {
+        match self {
+            Layer::Dense(bits) => bits.insert(r as usize),
+            Layer::Sparse(set) => set.insert(r),
+        }
+    }
+}
+
+/// Set of boards indexed by rank, one layer per stone count, flat bit arrays where they fit
+#[derive(Debug, Clone)]
+pub struct RankTable<G: Geometry> {
+    ranker: Ranker<G>,
+    layers: Vec<Option<Layer>>,
+    /// Boards with too many others of their stone count to rank, canonical if `canonical`
+    unranked: FxHashSet<Board<G>>,
+    /// Index by canonical rank, so a board and its symmetries share one bit
+    canonical: bool,
+}
+
+impl<G: Geometry> Default for RankTable<G> {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+impl<G: Geometry> RankTable<G> {
+    pub fn new(canonical: bool) -> Self {
+        let ranker = Ranker::new();
+        let layers = vec![None; ranker.cells() + 1];
+        Self {
+            ranker,
+            layers,
+            unranked: FxHashSet::default(),
+            canonical,
+        }
+    }
+    fn rank(&self, board: &Board<G>) -> Option<(usize, u64)> {
+        if self.canonical {
+            self.ranker.rank_canonical(board)
+        } else {
+            self.ranker.rank(board)
+        }
+    }
+    /// The board as it is kept in `unranked`
+    fn key(&self, board: &Board<G>) -> Board<G> {
+        if self.canonical {
+            board.canonicalize()
+        } else {
+            *board
+        }
+    }
+    pub fn contains(&self, board: &Board<G>) -> bool {
+        match self.rank(board) {
+            Some((k, r)) => self.layers[k]
+                .as_ref()
+                .map(|layer| layer.contains(r))
+                .unwrap_or(false),
+            None => self.unranked.contains(&self.key(board)),
+        }
+    }
+    /// Returns true if the board was not already present
+    pub fn insert(&mut self, board: &Board<G>) -> bool {
+        match self.rank(board) {
+            Some((k, r)) => {
+                let count = self.ranker.count(k).expect("Ranked");
+                self.layers[k]
+                    .get_or_insert_with(|| Layer::new(count))
+                    .insert(r)
+            }
+            None => {
+                let key = self.key(board);
+                self.unranked.insert(key)
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::{collections::hash_map::Entry, fmt};
+
+use fxhash::FxHashMap;
+use itertools::Itertools;
+
+use crate::{
+    board::{Board, ParseBoardError, Unreachable},
+    geometry::Geometry,
+    strategy::Jump,
+};
+
+/// Each board found, with the number of jumps from where its search started, and the board next
+/// to it on the way back there with the jump between them
+type Visited<G> = FxHashMap<Board<G>, (usize, Option<(Board<G>, Jump)>)>;
+
+/// The boards one jump further out from `layer` not found before, recorded in `visited`
+fn grow<G: Geometry>(
+    layer: &[Board<G>],
+    visited: &mut Visited<G>,
+    neighbours: impl Fn(&Board<G>) -> Vec<(Jump, Board<G>)>,
+) -> Vec<Board<G>> {
+    let mut next_layer = Vec::new();
+    for board in layer {
+        let depth = visited[board].0 + 1;
+        for (jump, next) in neighbours(board) {
+            if let Entry::Vacant(entry) = visited.entry(next) {
+                entry.insert((depth, Some((*board, jump))));
+                next_layer.push(next);
+            }
+        }
+    }
+    next_layer
+}
+
+/// Fewest jumps that play `a` into `b`, None if there are none. Searches forwards from `a` and
+/// backwards from `b` a layer of jumps at a time, always growing the smaller frontier, until a
+/// board is found by both. Boards either side that an invariant rules out are never kept
+pub fn reachable<G: Geometry>(a: &Board<G>, b: &Board<G>) -> Option<Vec<Jump>> {
+    a.reachability(b).ok()?;
+    if a == b {
+        return Some(Vec::new());
+    }
+    let mut forward: Visited<G> = FxHashMap::default();
+    let mut backward: Visited<G> = FxHashMap::default();
+    forward.insert(*a, (0, None));
+    backward.insert(*b, (0, None));
+    let (mut ahead, mut behind) = (vec![*a], vec![*b]);
+    // A long jump takes more than one stone, so layers can't be told apart by stone count and
+    // every new layer is checked against the other side
+    let meet = loop {
+        if ahead.is_empty() || behind.is_empty() {
+            return None;
+        }
+        let (grown, other) = if ahead.len() <= behind.len() {
+            ahead = grow(&ahead, &mut forward, |board| {
+                board
+                    .all_valid_moves()
+                    .map(|jump| {
+                        let (from, _, to) = jump;
+                        let next = board.apply_move(from, to).expect("Valid by construction");
+                        (jump, next)
+                    })
+                    .filter(|(_, next)| next.may_reach(b))
+                    .collect()
+            });
+            (&ahead, &backward)
+        } else {
+            behind = grow(&behind, &mut backward, |board| {
+                board
+                    .predecessors()
+                    .into_iter()
+                    .filter(|(_, prev)| a.may_reach(prev))
+                    .collect()
+            });
+            (&behind, &forward)
+        };
+        // The whole layer is in, so the shortest way through it is the shortest of all
+        let found = grown
+            .iter()
+            .filter(|board| other.contains_key(board))
+            .min_by_key(|board| forward[board].0 + backward[board].0);
+        if let Some(&meet) = found {
+            break meet;
+        }
+    };
+
+    let mut jumps = Vec::with_capacity(forward[&meet].0 + backward[&meet].0);
+    let mut at = meet;
+    while let (_, Some((prev, jump))) = forward[&at] {
+        jumps.push(jump);
+        at = prev;
+    }
+    jumps.reverse();
+    let mut at = meet;
+    while let (_, Some((next, jump))) = backward[&at] {
+        jumps.push(jump);
+        at = next;
+    }
+    Some(jumps)
+}
+
+/// Why no jumps play one board into another
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Mismatch {
+    /// An invariant rules it out without searching
+    Invariant(Unreachable),
+    /// Every invariant agrees but the search found no way
+    NoJumps,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::Invariant(why) => write!(f, "{}", why),
+            Mismatch::NoJumps => write!(f, "no sequence of jumps gets there"),
+        }
+    }
+}
+
+/// What was played between `a` and `b`: the jump if one does it, or else the fewest jumps that
+/// do, a long jump counting as one however many stones it takes. Empty if they are the same
+/// board
+pub fn diff<G: Geometry>(a: &Board<G>, b: &Board<G>) -> Result<Vec<Jump>, Mismatch> {
+    if let Some(jump) = a.jump_to(b) {
+        return Ok(vec![jump]);
+    }
+    a.reachability(b).map_err(Mismatch::Invariant)?;
+    reachable(a, b).ok_or(Mismatch::NoJumps)
+}
+
+/// Boards written one after another, blank lines between them are optional
+pub fn parse_record<G: Geometry>(text: &str) -> Result<Vec<Board<G>>, ParseBoardError> {
+    text.lines()
+        .filter(|l| !l.trim().is_empty())
+        .chunks(G::ROWS as usize)
+        .into_iter()
+        .map(|mut rows| rows.join("\n").parse())
+        .collect()
+}
+
+/// Jumps between consecutive boards of a record, or the index of the first board that doesn't
+/// follow from the one before and why
+pub fn replay<G: Geometry>(boards: &[Board<G>]) -> Result<Vec<Jump>, (usize, Mismatch)> {
+    boards
+        .iter()
+        .tuple_windows()
+        .enumerate()
+        .try_fold(Vec::new(), |mut jumps, (i, (a, b))| {
+            jumps.extend(diff(a, b).map_err(|why| (i + 1, why))?);
+            Ok(jumps)
+        })
+}
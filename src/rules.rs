@@ -0,0 +1,120 @@
+use std::{fmt::Debug, hash::Hash, marker::PhantomData, ops::Range};
+
+use crate::{
+    board::Position,
+    geometry::{Geometry, Symmetry, ORTHOGONAL_DIRECTIONS},
+};
+
+/// Which stones a stone may jump over, by colour
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Capture {
+    Any,
+    /// Only stones of a different colour
+    Different,
+    /// Only stones of the same colour
+    Same,
+}
+
+/// Changes to how jumps work, applied to a geometry with `Variant`
+pub trait RuleSet: Copy + Clone + Debug + Default + PartialEq + Eq + Hash + Send + Sync + 'static {
+    /// Directions a jump may take, None keeps the geometry's
+    const DIRECTIONS: Option<&'static [(i8, i8)]> = None;
+    /// Jump over a whole line of stones into the first hole after it, removing all of them
+    const LONG_JUMPS: bool = false;
+    /// Number of stone colours, 1 keeps the geometry's
+    const COLOURS: u8 = 1;
+    /// Any keeps the geometry's
+    const CAPTURE: Capture = Capture::Any;
+}
+
+/// Square lattice with diagonal jumps as well as orthogonal ones
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Diagonal;
+impl RuleSet for Diagonal {
+    const DIRECTIONS: Option<&'static [(i8, i8)]> = Some(&[
+        (1, 0),
+        (0, 1),
+        (-1, 0),
+        (0, -1),
+        (1, 1),
+        (-1, 1),
+        (-1, -1),
+        (1, -1),
+    ]);
+}
+
+/// Only along the x and y axes, on the triangular lattice this drops one of the three lines
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Orthogonal;
+impl RuleSet for Orthogonal {
+    const DIRECTIONS: Option<&'static [(i8, i8)]> = Some(ORTHOGONAL_DIRECTIONS);
+}
+
+/// Jump over any number of stones in a row
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Long;
+impl RuleSet for Long {
+    const LONG_JUMPS: bool = true;
+}
+
+/// Stones in `N` colours, which only matter for goals
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Colours<const N: u8>;
+impl<const N: u8> RuleSet for Colours<N> {
+    const COLOURS: u8 = N;
+}
+
+/// Stones in `N` colours, a stone may only jump one of another colour
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct OtherColour<const N: u8>;
+impl<const N: u8> RuleSet for OtherColour<N> {
+    const COLOURS: u8 = N;
+    const CAPTURE: Capture = Capture::Different;
+}
+
+/// Stones in `N` colours, a stone may only jump one of its own colour
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SameColour<const N: u8>;
+impl<const N: u8> RuleSet for SameColour<N> {
+    const COLOURS: u8 = N;
+    const CAPTURE: Capture = Capture::Same;
+}
+
+/// Geometry `G` played with the rules `R`
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Variant<G, R>(PhantomData<(G, R)>);
+
+impl<G: Geometry, R: RuleSet> Geometry for Variant<G, R> {
+    const ROWS: u8 = G::ROWS;
+    fn row(y: u8) -> Range<u8> {
+        G::row(y)
+    }
+    const DIRECTIONS: &'static [(i8, i8)] = match R::DIRECTIONS {
+        Some(directions) => directions,
+        None => G::DIRECTIONS,
+    };
+    const SYMMETRIES: &'static [Symmetry] = G::SYMMETRIES;
+    fn layout(p: Position) -> (f32, f32) {
+        G::layout(p)
+    }
+    const LONG_JUMPS: bool = R::LONG_JUMPS || G::LONG_JUMPS;
+    const RESTRICTED: bool = R::DIRECTIONS.is_some() || G::RESTRICTED;
+    const COLOURS: u8 = if R::COLOURS > 1 { R::COLOURS } else { G::COLOURS };
+    const CAPTURE: Capture = match R::CAPTURE {
+        Capture::Any => G::CAPTURE,
+        capture => capture,
+    };
+
+    fn blocked(p: Position) -> bool {
+        G::blocked(p)
+    }
+    fn valid_pos(p: Position) -> bool {
+        G::valid_pos(p)
+    }
+    fn get_idx(p: Position) -> Option<usize> {
+        G::get_idx(p)
+    }
+    fn get_pos(i: usize) -> Option<Position> {
+        G::get_pos(i)
+    }
+}
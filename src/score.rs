@@ -0,0 +1,209 @@
+use std::{
+    fmt,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    str::FromStr,
+    time::Instant,
+};
+
+use itertools::Itertools;
+
+use crate::{
+    board::{Board, Position},
+    geometry::Geometry,
+};
+
+/// Every scored game played here, one line per game
+pub const HISTORY_FILE: &str = "history.txt";
+
+/// Points for finishing with 1, 2, 3 and 4 stones, 10 fewer for each stone after that
+const STONE_POINTS: [u32; 4] = [1000, 500, 250, 100];
+/// For a last stone on the hole the game started from
+const COMPLEMENT_BONUS: u32 = 500;
+const HINT_PENALTY: u32 = 50;
+const UNDO_PENALTY: u32 = 20;
+/// One point lost every this many seconds
+const SECONDS_PER_POINT: u64 = 5;
+
+/// What the tables in the restaurants say about the stones left
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rating {
+    Genius,
+    PurtySmart,
+    Ignoramus,
+    PlainDumb,
+}
+
+impl Rating {
+    pub fn for_stones(stones: usize) -> Self {
+        match stones {
+            0 | 1 => Rating::Genius,
+            2 => Rating::PurtySmart,
+            3 => Rating::Ignoramus,
+            _ => Rating::PlainDumb,
+        }
+    }
+}
+
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Rating::Genius => "you're genius",
+            Rating::PurtySmart => "you're purty smart",
+            Rating::Ignoramus => "you're just plain eg-no-ra-moose",
+            Rating::PlainDumb => "you're just plain dumb",
+        })
+    }
+}
+
+/// A finished game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    /// The board and rules from the command line
+    pub board: String,
+    /// Holes the game started with
+    pub start: Vec<Position>,
+    pub stones: usize,
+    /// The last stone is on the only hole the game started with
+    pub complement: bool,
+    pub hints: u32,
+    pub undos: u32,
+    pub seconds: u64,
+}
+
+impl Session {
+    pub fn rating(&self) -> Rating {
+        Rating::for_stones(self.stones)
+    }
+    pub fn score(&self) -> u32 {
+        let stones = match self.stones.checked_sub(1) {
+            Some(i) if i < STONE_POINTS.len() => STONE_POINTS[i],
+            Some(i) => STONE_POINTS[STONE_POINTS.len() - 1]
+                .saturating_sub(10 * (i + 1 - STONE_POINTS.len()) as u32),
+            None => 0,
+        };
+        let bonus = if self.complement { COMPLEMENT_BONUS } else { 0 };
+        (stones + bonus)
+            .saturating_sub(self.hints * HINT_PENALTY)
+            .saturating_sub(self.undos * UNDO_PENALTY)
+            .saturating_sub((self.seconds / SECONDS_PER_POINT) as u32)
+    }
+}
+
+/// Tab separated, a line of the history file
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let start = self.start.iter().map(|p| format!("{},{}", p.x, p.y)).join(" ");
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.board, start, self.stones, self.complement, self.hints, self.undos, self.seconds
+        )
+    }
+}
+
+impl FromStr for Session {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields = s.split('\t').collect_vec();
+        if fields.len() != 7 {
+            return Err(());
+        }
+        Ok(Self {
+            board: fields[0].to_owned(),
+            start: fields[1]
+                .split_whitespace()
+                .map(Position::parse)
+                .collect::<Option<_>>()
+                .ok_or(())?,
+            stones: fields[2].parse().map_err(|_| ())?,
+            complement: fields[3].parse().map_err(|_| ())?,
+            hints: fields[4].parse().map_err(|_| ())?,
+            undos: fields[5].parse().map_err(|_| ())?,
+            seconds: fields[6].parse().map_err(|_| ())?,
+        })
+    }
+}
+
+/// Counts what a game being played will be scored on, scored once when no jump is left
+#[derive(Debug, Clone)]
+pub struct Tally {
+    started: Instant,
+    pub hints: u32,
+    pub undos: u32,
+    finished: bool,
+}
+
+impl Default for Tally {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            hints: 0,
+            undos: 0,
+            finished: false,
+        }
+    }
+}
+
+impl Tally {
+    /// The session if `board` ends the game started from `start`, only the first time. `name`
+    /// is the board and rules played
+    pub fn finish<G: Geometry>(
+        &mut self,
+        name: &str,
+        start: &Board<G>,
+        board: &Board<G>,
+    ) -> Option<Session> {
+        if self.finished || board.all_valid_moves().next().is_some() {
+            return None;
+        }
+        self.finished = true;
+        let start = start.iter_holes().collect_vec();
+        Some(Session {
+            board: name.to_owned(),
+            complement: board.count() == 1
+                && start.len() == 1
+                && board.at(start[0]) == Some(true),
+            start,
+            stones: board.count(),
+            hints: self.hints,
+            undos: self.undos,
+            seconds: self.started.elapsed().as_secs(),
+        })
+    }
+}
+
+/// Append to the history file
+pub fn record(session: &Session) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(HISTORY_FILE)?;
+    writeln!(file, "{}", session)
+}
+
+/// Every session in the history file, oldest first
+pub fn history() -> Vec<Session> {
+    fs::read_to_string(HISTORY_FILE)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.parse().ok())
+        .collect()
+}
+
+/// Highest scoring session for each board and start
+pub fn personal_bests(history: &[Session]) -> Vec<&Session> {
+    history
+        .iter()
+        .into_group_map_by(|session| (&session.board, &session.start))
+        .into_values()
+        .map(|sessions| {
+            sessions
+                .into_iter()
+                .max_by_key(|session| session.score())
+                .expect("Groups aren't empty")
+        })
+        .sorted_by_key(|session| (&session.board, &session.start))
+        .collect()
+}
@@ -0,0 +1,148 @@
+use itertools::Itertools;
+
+use crate::{
+    board::Board,
+    commutation::{lex_normal_form, stays_normal},
+    geometry::{Geometry, Symmetry},
+    goal::Goal,
+    solver::Solver,
+    strategy::Jump,
+};
+
+/// Which solutions count as the same
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Dedup {
+    /// Solutions mapped onto each other by a symmetry keeping the start and the goal
+    pub symmetry: bool,
+    /// Solutions that only differ in the order of jumps touching different cells
+    pub commuting: bool,
+}
+
+/// `x,y-x,y` for each jump, from and to
+pub fn solution_text(jumps: &[Jump]) -> String {
+    jumps
+        .iter()
+        .map(|(from, _, to)| format!("{},{}-{},{}", from.x, from.y, to.x, to.y))
+        .join(" ")
+}
+
+/// Depth first stream of the sequences of jumps from a board that reach a goal, smallest first.
+/// Only the current line of play is kept, the solver prunes boards that can't reach the goal
+#[derive(Debug, Clone)]
+pub struct Solutions<G: Geometry> {
+    solver: Solver<G>,
+    dedup: Dedup,
+    /// Symmetries other than the identity keeping both the start and the goal
+    symmetries: Vec<Symmetry>,
+    /// Board after each prefix of `path`, with the jumps from it still to try, biggest first
+    stack: Vec<(Board<G>, Vec<Jump>)>,
+    path: Vec<Jump>,
+    /// The start already reaches the goal, the empty solution is still to come
+    solved: bool,
+}
+
+impl<G: Geometry> Solutions<G> {
+    pub fn new(board: Board<G>, goal: Goal<G>, dedup: Dedup) -> Self {
+        let symmetries = Board::<G>::symmetries()
+            .skip(1)
+            .filter(|&sym| board.map(sym) == Some(board) && keeps_goal(&goal, sym))
+            .collect();
+        let mut solver = Solver::new(goal);
+        let solved = goal.reached(&board);
+        let stack = if !solved && solver.solvable(&board) {
+            vec![(board, Self::jumps(&board))]
+        } else {
+            Vec::new()
+        };
+        Self {
+            solver,
+            dedup,
+            symmetries,
+            stack,
+            path: Vec::with_capacity(board.count()),
+            solved,
+        }
+    }
+    fn jumps(board: &Board<G>) -> Vec<Jump> {
+        board.all_valid_moves().sorted().rev().collect()
+    }
+
+    /// No symmetry maps the solution onto a smaller one
+    fn smallest_image(&self, solution: &[Jump]) -> bool {
+        self.symmetries.iter().all(|&sym| {
+            let image = solution
+                .iter()
+                .map(|&(from, over, to)| (sym(from), sym(over), sym(to)))
+                .collect_vec();
+            let image = if self.dedup.commuting {
+                lex_normal_form(&image)
+            } else {
+                image
+            };
+            solution <= &image[..]
+        })
+    }
+    /// Without commuting, a prefix already bigger than one of its images never gets smaller
+    fn smallest_prefix(&self, prefix: &[Jump]) -> bool {
+        self.dedup.commuting
+            || self.symmetries.iter().all(|&sym| {
+                prefix
+                    .iter()
+                    .map(|&(from, over, to)| (sym(from), sym(over), sym(to)))
+                    .ge(prefix.iter().copied())
+            })
+    }
+}
+
+fn keeps_goal<G: Geometry>(goal: &Goal<G>, sym: Symmetry) -> bool {
+    match *goal {
+        Goal::Hole(p) => sym(p) == p,
+        Goal::Pattern(target) => target.map(sym) == Some(target),
+        Goal::Custom(_) => false,
+        _ => goal.symmetric(),
+    }
+}
+
+impl<G: Geometry> Iterator for Solutions<G> {
+    type Item = Vec<Jump>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.solved {
+            self.solved = false;
+            return Some(Vec::new());
+        }
+        loop {
+            let (board, jumps) = self.stack.last_mut()?;
+            let jump = match jumps.pop() {
+                Some(jump) => jump,
+                None => {
+                    self.stack.pop();
+                    self.path.pop();
+                    continue;
+                }
+            };
+            let (from, _, to) = jump;
+            let next = board.apply_move(from, to).expect("Valid by construction");
+            if self.dedup.commuting && !stays_normal(&self.path, jump) {
+                continue;
+            }
+            if !self.solver.solvable(&next) {
+                continue;
+            }
+            self.path.push(jump);
+            if self.dedup.symmetry && !self.smallest_prefix(&self.path) {
+                self.path.pop();
+                continue;
+            }
+            if self.solver.goal().reached(&next) {
+                let solution = self.path.clone();
+                self.path.pop();
+                if !self.dedup.symmetry || self.smallest_image(&solution) {
+                    return Some(solution);
+                }
+                continue;
+            }
+            self.stack.push((next, Self::jumps(&next)));
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::cmp::Reverse;
+
+use boolinator::Boolinator;
+use itertools::Itertools;
+
+use crate::{
+    board::{Board, Position, PositionClass},
+    geometry::Geometry,
+    goal::Goal,
+    packages::{self, Macro},
+    pagoda::Pagodas,
+    rank::RankTable,
+    strategy::Jump,
+};
+
+/// Memoized depth first search for a sequence of jumps reaching the goal
+#[derive(Debug, Clone)]
+pub struct Solver<G: Geometry> {
+    goal: Goal<G>,
+    /// Pagoda functions towards the finishes each position class could reach
+    pagodas: Vec<(PositionClass, Pagodas<G>)>,
+    /// Boards already searched, canonical if the goal is symmetric
+    seen: RankTable<G>,
+    /// The boards in `seen` that can be solved
+    solvable: RankTable<G>,
+}
+
+impl<G: Geometry> Default for Solver<G> {
+    fn default() -> Self {
+        Self::new(Goal::AnyOne)
+    }
+}
+
+impl<G: Geometry> Solver<G> {
+    pub fn new(goal: Goal<G>) -> Self {
+        Self {
+            goal,
+            pagodas: Vec::with_capacity(4),
+            seen: RankTable::new(goal.symmetric()),
+            solvable: RankTable::new(goal.symmetric()),
+        }
+    }
+    pub fn goal(&self) -> &Goal<G> {
+        &self.goal
+    }
+
+    /// Position class is invariant, so a whole search uses the same pagodas
+    fn pagodas_for(&mut self, board: &Board<G>) -> usize {
+        let class = board.position_class();
+        if let Some(i) = self.pagodas.iter().position(|&(c, _)| c == class) {
+            i
+        } else {
+            self.pagodas
+                .push((class, Pagodas::for_goal(&self.goal, board)));
+            self.pagodas.len() - 1
+        }
+    }
+
+    pub fn solvable(&mut self, board: &Board<G>) -> bool {
+        let pg = self.pagodas_for(board);
+        let values = self.pagodas[pg].1.values(board);
+        self.search(pg, board, &values)
+    }
+    fn search(&mut self, pg: usize, board: &Board<G>, values: &[i64]) -> bool {
+        if self.goal.reached(board) {
+            return true;
+        }
+        if self.seen.contains(board) {
+            return self.solvable.contains(board);
+        }
+        // Position class and pagoda functions rule out every finish, skip the whole subtree
+        let found = self.goal.alive(board)
+            && self.pagodas[pg].1.finishable(values, board.count())
+            && board.all_valid_moves().any(|(from, over, to)| {
+                let next = board.apply_move(from, to).expect("Valid by construction");
+                let mut values = values.to_vec();
+                self.pagodas[pg].1.apply(&mut values, from, over, to);
+                self.search(pg, &next, &values)
+            });
+        self.seen.insert(board);
+        if found {
+            self.solvable.insert(board);
+        }
+        found
+    }
+
+    /// (from, over, to) jumps that reach the goal
+    pub fn solution(&mut self, board: &Board<G>) -> Option<Vec<(Position, Position, Position)>> {
+        if !self.solvable(board) {
+            return None;
+        }
+        let mut board = *board;
+        let mut moves = Vec::with_capacity(board.count());
+        while !self.goal.reached(&board) {
+            let (mv, next) = board
+                .all_valid_moves()
+                .map(|(from, over, to)| {
+                    let next = board.apply_move(from, to).expect("Valid by construction");
+                    ((from, over, to), next)
+                })
+                .collect_vec()
+                .into_iter()
+                .find(|(_, next)| self.solvable(next))
+                .expect("A solvable board has a solvable child");
+            moves.push(mv);
+            board = next;
+        }
+        Some(moves)
+    }
+    /// A solution searched with packages as moves of their own, tried before any single jump
+    /// and the biggest first. Boards halfway through a package are never searched from, so a
+    /// board that packages clear is solved without going through the orders of their jumps
+    pub fn macro_solution(&mut self, board: &Board<G>) -> Option<Vec<Macro>> {
+        let pg = self.pagodas_for(board);
+        let values = self.pagodas[pg].1.values(board);
+        let mut failed = RankTable::new(self.goal.symmetric());
+        let mut path = Vec::with_capacity(board.count());
+        self.macro_search(pg, board, &values, &mut failed, &mut path)
+            .as_some(path)
+    }
+    fn macro_search(
+        &mut self,
+        pg: usize,
+        board: &Board<G>,
+        values: &[i64],
+        failed: &mut RankTable<G>,
+        path: &mut Vec<Macro>,
+    ) -> bool {
+        if self.goal.reached(board) {
+            return true;
+        }
+        // Unsolvable boards the jump by jump search already found are skipped too
+        if failed.contains(board) || (self.seen.contains(board) && !self.solvable.contains(board))
+        {
+            return false;
+        }
+        if self.goal.alive(board) && self.pagodas[pg].1.finishable(values, board.count()) {
+            let packages = packages::matches(board)
+                .into_iter()
+                .sorted_by_key(|placement| Reverse(placement.clears.len()))
+                .map(Macro::Package);
+            let steps = packages.chain(board.all_valid_moves().map(Macro::Jump)).collect_vec();
+            for step in steps {
+                let mut next = *board;
+                let mut values = values.to_vec();
+                for &(from, over, to) in step.jumps() {
+                    next = next.apply_move(from, to).expect("Valid by construction");
+                    self.pagodas[pg].1.apply(&mut values, from, over, to);
+                }
+                path.push(step);
+                if self.macro_search(pg, &next, &values, failed, path) {
+                    return true;
+                }
+                path.pop();
+            }
+        }
+        failed.insert(board);
+        false
+    }
+}
+
+/// Fool's solitaire: the most stones `board` can be left with once no jump is possible,
+/// and the jumps that get there
+pub fn fools_solitaire<G: Geometry>(board: &Board<G>) -> Option<(usize, Vec<Jump>)> {
+    (1..=board.count())
+        .rev()
+        .find_map(|n| Some((n, Solver::new(Goal::Stuck(n)).solution(board)?)))
+}
@@ -0,0 +1,95 @@
+use std::{convert::TryFrom, fmt};
+
+use itertools::Itertools;
+use rayon::prelude::*;
+
+use crate::{
+    board::{Board, Position},
+    geometry::Geometry,
+    goal::Goal,
+    solver::Solver,
+};
+
+/// Every start with `holes` empty holes, one per symmetry class, stones in their start colours
+pub fn starts<G: Geometry>(holes: usize) -> impl Iterator<Item = Board<G>> {
+    Board::<G>::iter_all()
+        .combinations(holes)
+        .map(Board::start_with)
+        .unique_by(Board::canonicalize)
+}
+
+/// Every placement of the holes in `shape` anywhere on the board, one per symmetry class
+pub fn shape_starts<G: Geometry>(shape: &[Position]) -> Vec<Board<G>> {
+    let size = Board::<G>::iter_all()
+        .map(|p| p.x.max(p.y) as i16 + 1)
+        .max()
+        .unwrap_or(0);
+    (-size..size)
+        .cartesian_product(-size..size)
+        .filter_map(|(dx, dy)| {
+            shape
+                .iter()
+                .map(|p| {
+                    Position::try_from((p.x as i16 + dx, p.y as i16 + dy))
+                        .ok()
+                        .filter(|&p| Board::<G>::get_idx(p).is_some())
+                })
+                .collect::<Option<Vec<_>>>()
+        })
+        .map(Board::start_with)
+        .unique_by(Board::canonicalize)
+        .collect()
+}
+
+/// Where a start can finish with a single stone
+#[derive(Debug, Clone)]
+pub struct StartReport<G: Geometry> {
+    pub start: Board<G>,
+    pub finishes: Vec<Position>,
+}
+
+impl<G: Geometry> StartReport<G> {
+    pub fn solvable(&self) -> bool {
+        !self.finishes.is_empty()
+    }
+}
+
+impl<G: Geometry> fmt::Display for StartReport<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let holes = self.start.iter_holes().map(|p| (p.x, p.y)).collect_vec();
+        if self.solvable() {
+            let finishes = self.finishes.iter().map(|p| (p.x, p.y)).collect_vec();
+            write!(f, "holes {:?} finish on {:?}", holes, finishes)
+        } else {
+            write!(f, "holes {:?} unsolvable", holes)
+        }
+    }
+}
+
+/// Solve every start for every finishing hole, one solver per hole shared between starts
+pub fn report<G: Geometry>(starts: impl IntoIterator<Item = Board<G>>) -> Vec<StartReport<G>> {
+    let starts = starts.into_iter().collect_vec();
+    let solved = Board::<G>::iter_all()
+        .collect_vec()
+        .into_par_iter()
+        .map(|hole| {
+            let mut solver = Solver::new(Goal::Hole(hole));
+            starts
+                .iter()
+                .map(|start| start.may_reach(&Board::single(hole)) && solver.solvable(start))
+                .collect_vec()
+        })
+        .collect::<Vec<_>>();
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| StartReport {
+            start,
+            finishes: Board::<G>::iter_all()
+                .zip(&solved)
+                .filter(|(_, solved)| solved[i])
+                .map(|(hole, _)| hole)
+                .collect(),
+        })
+        .collect()
+}
@@ -0,0 +1,258 @@
+use std::fmt;
+
+use itertools::Itertools;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use rayon::prelude::*;
+
+use crate::{
+    board::{Board, Position},
+    geometry::Geometry,
+    goal::Goal,
+    solver::Solver,
+};
+
+/// (from, over, to)
+pub type Jump = (Position, Position, Position);
+
+/// A solitaire player
+pub trait Strategy<G: Geometry> {
+    /// The jump to make, None if there is none
+    fn choose(&mut self, board: &Board<G>) -> Option<Jump>;
+}
+
+/// Any jump, uniformly
+#[derive(Debug, Clone)]
+pub struct Random {
+    rng: StdRng,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl<G: Geometry> Strategy<G> for Random {
+    fn choose(&mut self, board: &Board<G>) -> Option<Jump> {
+        board
+            .all_valid_moves()
+            .collect_vec()
+            .choose(&mut self.rng)
+            .copied()
+    }
+}
+
+/// The jump leaving the lowest cost, ties broken at random
+#[derive(Debug, Clone)]
+pub struct Greedy<G: Geometry> {
+    cost: fn(&Board<G>) -> f32,
+    rng: StdRng,
+}
+
+impl<G: Geometry> Greedy<G> {
+    pub fn new(cost: fn(&Board<G>) -> f32, seed: u64) -> Self {
+        Self {
+            cost,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+    /// Keep the stones close to the middle of the board
+    pub fn central(seed: u64) -> Self {
+        Self::new(
+            |board| {
+                let cells = Board::<G>::iter_all().map(G::layout).collect_vec();
+                let n = cells.len() as f32;
+                let (cx, cy) = cells
+                    .iter()
+                    .fold((0., 0.), |(x, y), &(px, py)| (x + px / n, y + py / n));
+                board
+                    .iter_stones()
+                    .map(G::layout)
+                    .map(|(x, y)| (x - cx) * (x - cx) + (y - cy) * (y - cy))
+                    .sum()
+            },
+            seed,
+        )
+    }
+    /// Leave as few stones as possible without a neighbour
+    pub fn connected(seed: u64) -> Self {
+        Self::new(
+            |board| {
+                board
+                    .iter_stones()
+                    .filter(|&p| Board::<G>::adjacent(p).all(|q| board.at(q) != Some(true)))
+                    .count() as f32
+            },
+            seed,
+        )
+    }
+}
+
+impl<G: Geometry> Strategy<G> for Greedy<G> {
+    fn choose(&mut self, board: &Board<G>) -> Option<Jump> {
+        let costs = board
+            .all_valid_moves()
+            .map(|jump| {
+                let (from, _, to) = jump;
+                let next = board.apply_move(from, to).expect("Valid by construction");
+                (jump, (self.cost)(&next))
+            })
+            .collect_vec();
+        let best = costs
+            .iter()
+            .map(|&(_, cost)| cost)
+            .fold(f32::INFINITY, f32::min);
+        costs
+            .into_iter()
+            .filter(|&(_, cost)| cost <= best)
+            .map(|(jump, _)| jump)
+            .collect_vec()
+            .choose(&mut self.rng)
+            .copied()
+    }
+}
+
+/// Leaves as few stones as the board allows, using the solver
+#[derive(Debug, Clone)]
+pub struct Perfect<G: Geometry> {
+    /// `solvers[i]` finishes with at most `i + 1` stones
+    solvers: Vec<Solver<G>>,
+}
+
+impl<G: Geometry> Default for Perfect<G> {
+    fn default() -> Self {
+        Self {
+            solvers: Vec::new(),
+        }
+    }
+}
+
+impl<G: Geometry> Perfect<G> {
+    fn solver(&mut self, i: usize) -> &mut Solver<G> {
+        while self.solvers.len() <= i {
+            // One stone has the pagoda functions to prune with
+            let goal = match self.solvers.len() {
+                0 => Goal::AnyOne,
+                n => Goal::AtMost(n + 1),
+            };
+            self.solvers.push(Solver::new(goal));
+        }
+        &mut self.solvers[i]
+    }
+    /// Fewest stones `board` can be played down to
+    pub fn fewest(&mut self, board: &Board<G>) -> usize {
+        (0..board.count())
+            .find(|&i| self.solver(i).solvable(board))
+            .map_or(board.count(), |i| i + 1)
+    }
+}
+
+impl<G: Geometry> Strategy<G> for Perfect<G> {
+    fn choose(&mut self, board: &Board<G>) -> Option<Jump> {
+        board
+            .all_valid_moves()
+            .collect_vec()
+            .into_iter()
+            .min_by_key(|&(from, _, to)| {
+                let next = board.apply_move(from, to).expect("Valid by construction");
+                self.fewest(&next)
+            })
+    }
+}
+
+/// Play until stuck, returns the final board
+pub fn play_out<G: Geometry>(strategy: &mut impl Strategy<G>, start: Board<G>) -> Board<G> {
+    let mut board = start;
+    while let Some((from, _, to)) = strategy.choose(&board) {
+        board = board.apply_move(from, to).expect("Strategies choose valid jumps");
+    }
+    board
+}
+
+/// How many games finished with each number of stones
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Distribution {
+    /// `counts[k]` games finished with `k` stones
+    pub counts: Vec<usize>,
+}
+
+impl Distribution {
+    pub fn games(&self) -> usize {
+        self.counts.iter().sum()
+    }
+    pub fn mean(&self) -> f64 {
+        let total: usize = self.counts.iter().enumerate().map(|(k, n)| k * n).sum();
+        total as f64 / self.games().max(1) as f64
+    }
+}
+
+impl fmt::Display for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (stones, &n) in self.counts.iter().enumerate().filter(|&(_, &n)| n > 0) {
+            let share = 100. * n as f64 / self.games() as f64;
+            writeln!(f, "{:>3} stones: {:>6} {:>5.1}%", stones, n, share)?;
+        }
+        write!(f, "mean {:.2} stones over {} games", self.mean(), self.games())
+    }
+}
+
+/// Play `games` games from `start` and count how many stones each leaves
+pub fn distribution<G: Geometry>(
+    strategy: &mut impl Strategy<G>,
+    start: Board<G>,
+    games: usize,
+) -> Distribution {
+    let mut counts = vec![0; start.count() + 1];
+    for _ in 0..games {
+        counts[play_out(strategy, start).count()] += 1;
+    }
+    Distribution { counts }
+}
+
+/// Chance of reaching `goal` from each jump out of `board` when every jump after it is random,
+/// from `rollouts` games per jump. Every game has its own seed so the result only depends on `seed`
+pub fn estimates<G: Geometry>(
+    board: &Board<G>,
+    goal: &Goal<G>,
+    rollouts: usize,
+    seed: u64,
+) -> Vec<(Jump, f64)> {
+    board
+        .all_valid_moves()
+        .collect_vec()
+        .into_par_iter()
+        .enumerate()
+        .map(|(i, jump)| {
+            let (from, _, to) = jump;
+            let next = board.apply_move(from, to).expect("Valid by construction");
+            let reached = (0..rollouts)
+                .into_par_iter()
+                .filter(|&r| {
+                    let mut random = Random::new(seed ^ ((i as u64) << 32 | r as u64));
+                    rollout(&mut random, next, goal)
+                })
+                .count();
+            (jump, reached as f64 / rollouts.max(1) as f64)
+        })
+        .collect()
+}
+
+/// Whether the strategy reaches `goal` from `start`
+pub fn rollout<G: Geometry>(
+    strategy: &mut impl Strategy<G>,
+    start: Board<G>,
+    goal: &Goal<G>,
+) -> bool {
+    let mut board = start;
+    while !goal.reached(&board) {
+        match strategy.choose(&board) {
+            Some((from, _, to)) => {
+                board = board.apply_move(from, to).expect("Valid by construction")
+            }
+            None => return false,
+        }
+    }
+    true
+}
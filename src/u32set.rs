@@ -74,20 +74,6 @@ impl<T> FixedBitSet<T> {
     {
         self.len() == 0
     }
-    /// Reverse the first `i` bits
-    pub fn revn(self, i: usize) -> FixedBitSet<T>
-    where
-        T: ReverseBits,
-        T: Shr<usize, Output = T>,
-    {
-        Self::new(self.val.reverse_bits() >> (32 - i))
-    }
-    pub fn rev(self) -> FixedBitSet<T>
-    where
-        T: ReverseBits,
-    {
-        Self::new(self.val.reverse_bits())
-    }
 
     /// Infinite iterator of bits (after the last bit keeps returning false)
     pub fn iter_bits(self) -> impl Iterator<Item = bool>
@@ -176,29 +162,10 @@ count_ones_impl!(u8);
 count_ones_impl!(u64);
 count_ones_impl!(usize);
 
-pub trait ReverseBits {
-    fn reverse_bits(&self) -> Self;
-}
-macro_rules! reverse_bits_impl {
-    ($T:ty) => {
-        impl ReverseBits for $T {
-            fn reverse_bits(&self) -> $T {
-                <$T>::reverse_bits(*self) as _
-            }
-        }
-    };
-}
-reverse_bits_impl!(u32);
-reverse_bits_impl!(u16);
-reverse_bits_impl!(u8);
-reverse_bits_impl!(u64);
-reverse_bits_impl!(usize);
-
 pub trait SliceIndexBitSet<T> {
     type Output;
     fn get(self, slice: &FixedBitSet<T>) -> Option<Self::Output>;
     unsafe fn get_unchecked(self, slice: *const FixedBitSet<T>) -> Self::Output;
-    fn index(self, slice: &FixedBitSet<T>) -> Self::Output;
 }
 impl<T> SliceIndexBitSet<T> for usize
 where
@@ -214,10 +181,6 @@ where
     unsafe fn get_unchecked(self, slice: *const FixedBitSet<T>) -> bool {
         ((*slice).val & (T::from(0b1) << self)) != 0.into()
     }
-
-    fn index(self, slice: &FixedBitSet<T>) -> bool {
-        self.get(slice).unwrap()
-    }
 }
 impl<T> SliceIndexBitSet<T> for Range<usize>
 where
@@ -240,10 +203,6 @@ where
         //     val: ((*slice).val & mask) >> self.start,
         // }
     }
-
-    fn index(self, slice: &FixedBitSet<T>) -> FixedBitSet<T> {
-        self.get(slice).unwrap()
-    }
 }
 
 impl<T> SliceIndexBitSet<T> for RangeFrom<usize>
@@ -262,10 +221,6 @@ where
             val: (*slice).val >> self.start,
         }
     }
-
-    fn index(self, slice: &FixedBitSet<T>) -> FixedBitSet<T> {
-        self.get(slice).unwrap()
-    }
 }
 impl<T> SliceIndexBitSet<T> for RangeTo<usize>
 where
@@ -288,9 +243,5 @@ where
             val: (*slice).val & mask,
         }
     }
-
-    fn index(self, slice: &FixedBitSet<T>) -> FixedBitSet<T> {
-        self.get(slice).unwrap()
-    }
 }
 // TODO: other Range types
@@ -0,0 +1,142 @@
+use std::{fmt, time::Instant};
+
+use crate::{
+    board::{Board, Position},
+    geometry::Geometry,
+    rank::RankTable,
+    starts::starts,
+};
+
+/// How the last move counts in the two player game
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Play {
+    /// The player who can't move loses
+    Normal,
+    /// The player who can't move wins
+    Misere,
+}
+
+impl fmt::Display for Play {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Play::Normal => write!(f, "normal play"),
+            Play::Misere => write!(f, "misère"),
+        }
+    }
+}
+
+/// Result of perfect play for the player to move.
+/// Every jump removes a stone, so games always end and there are no draws
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Win,
+    Loss,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Win => write!(f, "win"),
+            Outcome::Loss => write!(f, "loss"),
+        }
+    }
+}
+
+/// Solves the two player game where both players jump on the same board in turn.
+/// Both players have the same moves, so only the board matters and symmetries can be shared
+#[derive(Debug, Clone)]
+pub struct Versus<G: Geometry> {
+    play: Play,
+    /// Boards already solved, canonical
+    seen: RankTable<G>,
+    /// The boards in `seen` that the player to move wins
+    wins: RankTable<G>,
+}
+
+impl<G: Geometry> Versus<G> {
+    pub fn new(play: Play) -> Self {
+        Self {
+            play,
+            seen: RankTable::new(true),
+            wins: RankTable::new(true),
+        }
+    }
+    pub fn play(&self) -> Play {
+        self.play
+    }
+
+    pub fn outcome(&mut self, board: &Board<G>) -> Outcome {
+        if self.wins(board) {
+            Outcome::Win
+        } else {
+            Outcome::Loss
+        }
+    }
+    fn wins(&mut self, board: &Board<G>) -> bool {
+        self.wins_by(board, None).expect("No deadline to run out")
+    }
+    /// None if `deadline` passed first. Only solved boards are recorded, so the next call carries
+    /// on where this one stopped
+    fn wins_by(&mut self, board: &Board<G>, deadline: Option<Instant>) -> Option<bool> {
+        if self.seen.contains(board) {
+            return Some(self.wins.contains(board));
+        }
+        if deadline.map_or(false, |deadline| Instant::now() > deadline) {
+            return None;
+        }
+        let mut moves = board.all_valid_moves().peekable();
+        let win = if moves.peek().is_none() {
+            self.play == Play::Misere
+        } else {
+            // Win if some jump leaves the other player lost
+            let mut win = false;
+            for (from, _, to) in moves {
+                let next = board.apply_move(from, to).expect("Valid by construction");
+                if !self.wins_by(&next, deadline)? {
+                    win = true;
+                    break;
+                }
+            }
+            win
+        };
+        self.seen.insert(board);
+        if win {
+            self.wins.insert(board);
+        }
+        Some(win)
+    }
+
+    /// (from, over, to) jumps with the outcome for the player making them, None for the jumps
+    /// not solved before `deadline`
+    pub fn outcomes(
+        &mut self,
+        board: &Board<G>,
+        deadline: Instant,
+    ) -> Vec<((Position, Position, Position), Option<Outcome>)> {
+        board
+            .all_valid_moves()
+            .map(|(from, over, to)| {
+                let next = board.apply_move(from, to).expect("Valid by construction");
+                let outcome = self.wins_by(&next, Some(deadline)).map(|other_wins| {
+                    if other_wins {
+                        Outcome::Loss
+                    } else {
+                        Outcome::Win
+                    }
+                });
+                ((from, over, to), outcome)
+            })
+            .collect()
+    }
+}
+
+/// Outcome for the first player from every single hole start, one per symmetry class
+pub fn report<G: Geometry>(play: Play) -> Vec<(Position, Outcome)> {
+    let mut versus = Versus::new(play);
+    starts::<G>(1)
+        .map(|start| {
+            let hole = start.iter_holes().next().expect("Has one hole");
+            (hole, versus.outcome(&start))
+        })
+        .collect()
+}